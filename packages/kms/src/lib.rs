@@ -46,8 +46,7 @@ where
             .generate_mac()
             .key_id(&self.key_id)
             .mac_algorithm(Self::spec())
-            // TODO: Prefer not to unwrap - async map for Paranoid?
-            .message(Blob::new(self.input.clone().risky_unwrap()))
+            .message(self.input.expose(|input| Blob::new(input.clone())))
             .send()
             .await
             .map(|response| response.mac.unwrap())
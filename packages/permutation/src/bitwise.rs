@@ -1,6 +1,6 @@
 use crate::PermutationKey;
-use bitvec::{array::BitArray, order::Msb0};
 use std::num::{NonZeroU128, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8};
+use vitaminc_protected::{Controlled, Protected, ProtectedBits};
 use zeroize::Zeroize;
 
 // TODO: Make this a private trait
@@ -13,19 +13,21 @@ macro_rules! impl_bitwise_permutable {
     ($N:literal, $int_type:ty, $array_size:expr) => {
         impl BitwisePermute<$N, $int_type> for PermutationKey<$N> {
             fn bitwise_permute(&self, mut input: $int_type) -> $int_type {
-                let bytes = input.to_be_bytes();
-                let arr: BitArray<[u8; $array_size], Msb0> = BitArray::new(bytes);
-                let out: BitArray<[u8; $array_size], Msb0> = self.iter().enumerate().fold(
-                    BitArray::new([0; $array_size]),
-                    |mut out, (i, k)| {
-                        out.set(i, *unsafe { arr.get_unchecked(k) });
-                        out
-                    },
-                );
+                let arr: ProtectedBits<$array_size> = Protected::new(input).into();
+                let out: ProtectedBits<$array_size> =
+                    self.iter()
+                        .enumerate()
+                        .fold(ProtectedBits::zeroed(), |mut out, (i, k)| {
+                            // Keys are permutations of 0..N so every index is in bounds
+                            if let Some(bit) = arr.get(k) {
+                                out.set(Protected::new(i), bit);
+                            }
+                            out
+                        });
 
                 input.zeroize();
 
-                <$int_type>::from_be_bytes(out.into_inner())
+                Protected::<$int_type>::from(out).expose(|x| *x)
             }
         }
     };
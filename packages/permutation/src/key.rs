@@ -98,6 +98,50 @@ where
     }
 }
 
+/// Keys are generated with a Fisher-Yates shuffle driven by the fuzzer's input so they are always valid permutations.
+#[cfg(feature = "arbitrary")]
+impl<'a, const N: usize> arbitrary::Arbitrary<'a> for PermutationKey<N>
+where
+    [u8; N]: IsPermutable,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut key = KeyInner::<N>::generate(identity);
+        for i in (1..N).rev() {
+            let mut j = u.int_in_range(0..=i)?;
+            key.update(|key| key.swap(i, j));
+            j.zeroize();
+        }
+        Ok(Self(key))
+    }
+}
+
+/// Keys shrink towards the identity permutation.
+#[cfg(feature = "proptest")]
+impl<const N: usize> proptest::arbitrary::Arbitrary for PermutationKey<N>
+where
+    [u8; N]: IsPermutable,
+{
+    type Parameters = ();
+    type Strategy = proptest::strategy::Map<
+        proptest::strategy::Shuffle<proptest::strategy::Just<Vec<u8>>>,
+        fn(Vec<u8>) -> Self,
+    >;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::{Just, Strategy};
+
+        Just(identity::<N>().to_vec())
+            .prop_shuffle()
+            .prop_map(|mut key| {
+                let inner = KeyInner::<N>::generate(|| {
+                    key.as_slice().try_into().expect("Key has N elements")
+                });
+                key.zeroize();
+                Self(inner)
+            })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -160,4 +204,35 @@ mod tests {
         test_key_complement::<32>();
         test_key_complement::<64>();
     }
+
+    #[cfg(any(feature = "arbitrary", feature = "proptest"))]
+    fn assert_valid<const N: usize>(key: PermutationKey<N>)
+    where
+        [u8; N]: IsPermutable,
+    {
+        let mut key = key.0.risky_unwrap();
+        key.sort();
+        assert_eq!(key, identity::<N>());
+    }
+
+    #[test]
+    #[cfg(feature = "arbitrary")]
+    fn arbitrary_key_case() {
+        let data: Vec<u8> = (0..=255).collect();
+        let mut u = arbitrary::Unstructured::new(&data);
+        for _ in 0..8 {
+            assert_valid(u.arbitrary::<PermutationKey<8>>().unwrap());
+            assert_valid(u.arbitrary::<PermutationKey<128>>().unwrap());
+        }
+        // Running out of data still produces a valid key
+        assert_valid(u.arbitrary::<PermutationKey<64>>().unwrap());
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn proptest_key_case(key in proptest::prelude::any::<PermutationKey<32>>()) {
+            assert_valid(key);
+        }
+    }
 }
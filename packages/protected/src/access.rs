@@ -0,0 +1,26 @@
+use core::fmt;
+
+/// Errors returned when accessing an adapter that restricts how often or how long its
+/// inner value can be used (e.g. [crate::Ephemeral], [crate::Expiring] and [crate::Limited]).
+///
+/// [crate::Controlled] methods that access the inner value panic with this error instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessError {
+    AlreadyUsed,
+    Expired,
+    BudgetExhausted,
+}
+
+// `Display` is implemented by hand because `thiserror` needs `std`
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AccessError::AlreadyUsed => "Secret has already been used",
+            AccessError::Expired => "Secret has expired",
+            AccessError::BudgetExhausted => "Secret usage budget is exhausted",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AccessError {}
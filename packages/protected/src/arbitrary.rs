@@ -0,0 +1,90 @@
+//! [Arbitrary] impls so that fuzzers can generate controlled types.
+//!
+//! Adapter stacks are generated from an arbitrary inner value so any stack that implements
+//! [Controlled] (e.g. `Equatable<Exportable<Protected<[u8; 32]>>>`) is supported.
+use crate::{
+    private::ControlledPrivate, Controlled, Equatable, Exportable, Protected, Scope, Usage,
+};
+use arbitrary::{Arbitrary, Result, Unstructured};
+use zeroize::Zeroize;
+
+impl<'a, T> Arbitrary<'a> for Protected<T>
+where
+    T: Arbitrary<'a> + Zeroize,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        T::arbitrary(u).map(Protected::new)
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        T::size_hint(depth)
+    }
+}
+
+macro_rules! impl_arbitrary {
+    ($($type:ident),+) => {
+        $(
+            impl<'a, T> Arbitrary<'a> for $type<T>
+            where
+                Self: Controlled,
+                <Self as ControlledPrivate>::Inner: Arbitrary<'a>,
+            {
+                fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+                    Arbitrary::arbitrary(u).map(Self::new)
+                }
+
+                fn size_hint(depth: usize) -> (usize, Option<usize>) {
+                    <<Self as ControlledPrivate>::Inner as Arbitrary<'a>>::size_hint(depth)
+                }
+            }
+        )+
+    };
+}
+
+impl_arbitrary!(Equatable, Exportable);
+
+impl<'a, T, S> Arbitrary<'a> for Usage<T, S>
+where
+    Self: Controlled,
+    <Self as ControlledPrivate>::Inner: Arbitrary<'a>,
+    S: Scope,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Arbitrary::arbitrary(u).map(Self::new)
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <<Self as ControlledPrivate>::Inner as Arbitrary<'a>>::size_hint(depth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adapter_stacks() {
+        let data = [7u8; 64];
+        let mut u = Unstructured::new(&data);
+
+        let x: Protected<[u8; 4]> = u.arbitrary().unwrap();
+        assert_eq!(x.risky_unwrap(), [7; 4]);
+
+        let x: Equatable<Exportable<Protected<u16>>> = u.arbitrary().unwrap();
+        assert_eq!(x.risky_unwrap(), 0x0707);
+
+        let x: Exportable<Equatable<Protected<[u8; 2]>>> = u.arbitrary().unwrap();
+        assert_eq!(x.risky_unwrap(), [7; 2]);
+
+        let x: Usage<Protected<u8>> = u.arbitrary().unwrap();
+        assert_eq!(x.risky_unwrap(), 7);
+    }
+
+    #[test]
+    fn test_size_hint() {
+        assert_eq!(
+            <Equatable<Protected<[u8; 32]>> as Arbitrary>::size_hint(0),
+            (32, Some(32))
+        );
+    }
+}
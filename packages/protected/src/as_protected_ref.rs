@@ -1,5 +1,9 @@
-use crate::{private::ControlledPrivate, Controlled};
-use std::borrow::Cow;
+use crate::{
+    private::{ControlledPrivate, InnerRef},
+    Controlled,
+};
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
 
 /// Trait for types that can be converted to a `ProtectedRef`.
 /// Conceptually similar to the `AsRef` trait in `std` but for `Protected` types.
@@ -27,6 +31,18 @@ use std::borrow::Cow;
 /// let pref: ProtectedRef<[u8]> = data.as_protected_ref();
 /// ```
 ///
+/// # Closure only types
+///
+/// Types that only lend their inner value to a closure (e.g. [crate::Masked]) don't implement `AsProtectedRef`.
+/// Use [Controlled::expose] instead.
+///
+/// ```compile_fail
+/// use vitaminc_protected::{AsProtectedRef, Controlled, Masked, ProtectedRef};
+///
+/// let key = Masked::new([1u8; 32]);
+/// let pref: ProtectedRef<[u8]> = key.as_protected_ref();
+/// ```
+///
 pub trait AsProtectedRef<'a, A: ?Sized> {
     fn as_protected_ref(&'a self) -> ProtectedRef<'a, A>;
 }
@@ -34,9 +50,12 @@ pub trait AsProtectedRef<'a, A: ?Sized> {
 impl<'a, T, A: ?Sized> AsProtectedRef<'a, A> for T
 where
     <T as ControlledPrivate>::Inner: AsRef<A>,
-    T: Controlled,
+    T: Controlled + InnerRef,
 {
+    #[cfg_attr(feature = "audit", track_caller)]
     fn as_protected_ref(&'a self) -> ProtectedRef<'a, A> {
+        #[cfg(feature = "audit")]
+        crate::audit::record::<T>(crate::audit::ExposureKind::ProtectedRef);
         ProtectedRef(self.inner().as_ref())
     }
 }
@@ -51,6 +70,7 @@ impl<'a> AsProtectedRef<'a, [u8]> for str {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a> AsProtectedRef<'a, [u8]> for Cow<'a, str> {
     fn as_protected_ref(&'a self) -> ProtectedRef<'a, [u8]> {
         ProtectedRef(self.as_bytes())
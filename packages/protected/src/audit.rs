@@ -0,0 +1,305 @@
+//! Audit hooks for recording when secrets are exposed at runtime.
+//!
+//! With the `audit` feature enabled, every path that hands an inner value to code outside of this crate
+//! calls a process-wide hook with an [ExposureEvent]:
+//!
+//! * [crate::Controlled::risky_unwrap], [crate::Controlled::expose] and [crate::Controlled::expose_mut]
+//! * the closure based transformations: [crate::Controlled::map], [crate::Controlled::zip],
+//!   [crate::Controlled::zip_ref], [crate::Controlled::update], [crate::Controlled::update_with]
+//!   and [crate::Controlled::update_with_ref]
+//! * [crate::AsProtectedRef::as_protected_ref]
+//! * serialization of exportable values (including envelopes)
+//! * the accessors of adapters that aren't [crate::Controlled] methods: `try_expose` on
+//!   [crate::Ephemeral], `Expiring` and `Limited` (recorded only when the access succeeds),
+//!   and `expose`, `update` and `open` on `Enclave` (with the `enclave` feature)
+//!
+//! Operations that only move a value between controlled types (e.g. [crate::Controlled::replace]
+//! and [crate::Controlled::iter]) are not recorded.
+//! Events describe where and what was exposed but never include the value itself.
+//!
+//! The default hook does nothing.
+//!
+//! # Example
+//!
+//! ```
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use vitaminc_protected::{audit, Controlled, Protected};
+//!
+//! static EXPOSURES: AtomicUsize = AtomicUsize::new(0);
+//! audit::set_hook(|_event| {
+//!     EXPOSURES.fetch_add(1, Ordering::Relaxed);
+//! });
+//!
+//! let collector = audit::AuditCollector::start();
+//! let key = Protected::new([0u8; 32]);
+//! let _ = key.risky_unwrap();
+//!
+//! let events = collector.events();
+//! assert_eq!(events.len(), 1);
+//! assert_eq!(events[0].kind, audit::ExposureKind::RiskyUnwrap);
+//! assert_eq!(events[0].location.file(), file!());
+//! assert!(EXPOSURES.load(Ordering::Relaxed) >= 1);
+//! # audit::clear_hook();
+//! ```
+use crate::private::ControlledPrivate;
+use std::{
+    cell::RefCell,
+    panic::Location,
+    rc::Rc,
+    sync::{Arc, RwLock},
+};
+
+/// How a secret was exposed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExposureKind {
+    /// The inner value was unwrapped with `risky_unwrap`.
+    RiskyUnwrap,
+    /// The inner value was passed by reference to a closure with `expose` or `expose_mut`.
+    Expose,
+    /// The inner value was passed to a closure that computes a new controlled value
+    /// (`map`, `zip`, `zip_ref`, `update`, `update_with` or `update_with_ref`).
+    Transform,
+    /// A [crate::ProtectedRef] was created with `as_protected_ref`.
+    ProtectedRef,
+    /// An `Enclave` was decrypted into a new controlled value with `open`.
+    Unwrap,
+    /// The inner value was serialized (for example, via [crate::Exportable] or [crate::Envelope]).
+    Export,
+}
+
+/// Details of a single exposure. The exposed value is never included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExposureEvent {
+    pub kind: ExposureKind,
+    /// Where the exposure happened.
+    /// For serialization, this is the serialization implementation rather than the caller.
+    pub location: &'static Location<'static>,
+    /// The name of the controlled type.
+    pub type_name: &'static str,
+    /// The name of the usage scope, if the type is a [crate::Usage].
+    pub scope: Option<&'static str>,
+}
+
+type Hook = dyn Fn(&ExposureEvent) + Send + Sync;
+
+static HOOK: RwLock<Option<Arc<Hook>>> = RwLock::new(None);
+
+thread_local! {
+    static COLLECTORS: RefCell<Vec<Rc<RefCell<Vec<ExposureEvent>>>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Set the process-wide audit hook, replacing any previous hook.
+pub fn set_hook<F>(hook: F)
+where
+    F: Fn(&ExposureEvent) + Send + Sync + 'static,
+{
+    *HOOK.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(hook));
+}
+
+/// Remove the process-wide audit hook.
+pub fn clear_hook() {
+    *HOOK.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Records exposure events on the current thread until it is dropped.
+///
+/// Collectors are independent of the process-wide hook so tests running in parallel
+/// can each assert on their own events.
+pub struct AuditCollector(Rc<RefCell<Vec<ExposureEvent>>>);
+
+impl AuditCollector {
+    pub fn start() -> Self {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        COLLECTORS.with(|collectors| collectors.borrow_mut().push(events.clone()));
+        Self(events)
+    }
+
+    /// The events recorded so far.
+    pub fn events(&self) -> Vec<ExposureEvent> {
+        self.0.borrow().clone()
+    }
+}
+
+impl Drop for AuditCollector {
+    fn drop(&mut self) {
+        COLLECTORS.with(|collectors| {
+            collectors
+                .borrow_mut()
+                .retain(|events| !Rc::ptr_eq(events, &self.0))
+        });
+    }
+}
+
+/// Record an exposure of `T` at the caller's location.
+#[track_caller]
+pub(crate) fn record<T: ControlledPrivate + ?Sized>(kind: ExposureKind) {
+    let event = ExposureEvent {
+        kind,
+        location: Location::caller(),
+        type_name: std::any::type_name::<T>(),
+        scope: T::scope_name(),
+    };
+
+    // Clone the hook so that it isn't called with the lock held
+    let hook = HOOK.read().unwrap_or_else(|e| e.into_inner()).clone();
+    if let Some(hook) = hook {
+        hook(&event);
+    }
+    COLLECTORS.with(|collectors| {
+        collectors
+            .borrow()
+            .iter()
+            .for_each(|events| events.borrow_mut().push(event))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AsProtectedRef, Controlled, Exportable, Protected, ProtectedRef, Scope, Usage};
+
+    struct KeyScope;
+    impl Scope for KeyScope {}
+
+    #[test]
+    fn test_risky_unwrap() {
+        let collector = AuditCollector::start();
+        let line = line!() + 1;
+        let _ = Protected::new([1u8; 4]).risky_unwrap();
+
+        let events = collector.events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, ExposureKind::RiskyUnwrap);
+        assert_eq!(events[0].location.file(), file!());
+        assert_eq!(events[0].location.line(), line);
+        assert_eq!(
+            events[0].type_name,
+            "vitaminc_protected::protected::Protected<[u8; 4]>"
+        );
+        assert_eq!(events[0].scope, None);
+    }
+
+    #[test]
+    fn test_scope_name() {
+        let collector = AuditCollector::start();
+        let x: Usage<Protected<[u8; 4]>, KeyScope> = Usage::new([1; 4]);
+        x.expose(|_| ());
+
+        let events = collector.events();
+        assert_eq!(events[0].kind, ExposureKind::Expose);
+        assert_eq!(
+            events[0].scope,
+            Some("vitaminc_protected::audit::tests::KeyScope")
+        );
+    }
+
+    #[test]
+    fn test_protected_ref_and_export() {
+        let collector = AuditCollector::start();
+        let x: Exportable<Protected<[u8; 4]>> = Exportable::new([1; 4]);
+        let line = line!() + 1;
+        let _: ProtectedRef<[u8]> = x.as_protected_ref();
+        serde_json::to_string(&x).unwrap();
+
+        let events = collector.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, ExposureKind::ProtectedRef);
+        assert_eq!(events[0].location.line(), line);
+        assert_eq!(events[1].kind, ExposureKind::Export);
+    }
+
+    #[test]
+    fn test_redacted_serialization_is_not_an_export() {
+        let collector = AuditCollector::start();
+        let x: Exportable<Protected<[u8; 4]>> = Exportable::new([1; 4]);
+        serde_json::to_string(&crate::redacted(&x, crate::Redaction::Placeholder)).unwrap();
+        assert!(collector.events().is_empty());
+    }
+
+    #[test]
+    fn test_transforms() {
+        let collector = AuditCollector::start();
+        let line = line!() + 1;
+        let x = Protected::new(1u8).map(|x| x + 1);
+        let mut x = x.zip(Protected::new(2u8), |x, y| x + y);
+        x.update(|x| *x += 1);
+        x.update_with(Protected::new(1u8), |x, y| *x += y);
+        x.update_with_ref(Protected::new([1u8]).as_protected_ref(), |x, y: &[u8]| {
+            *x += y[0]
+        });
+        let mut x = x.zip_ref(&Protected::new([1u8]), |x, y: &[u8]| x + y[0]);
+        x.replace(Protected::new(0));
+
+        let kinds: Vec<_> = collector.events().iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ExposureKind::Transform,
+                ExposureKind::Transform,
+                ExposureKind::Transform,
+                ExposureKind::Transform,
+                ExposureKind::Transform,
+                ExposureKind::Transform,
+                ExposureKind::ProtectedRef,
+                ExposureKind::Transform,
+                ExposureKind::Transform,
+                ExposureKind::ProtectedRef,
+            ]
+        );
+        assert_eq!(collector.events()[0].location.line(), line);
+    }
+
+    #[test]
+    fn test_adapter_accessors() {
+        use crate::{AccessError, Ephemeral, Limited};
+
+        let collector = AuditCollector::start();
+        let token: Ephemeral<Protected<[u8; 4]>> = Ephemeral::new([1; 4]);
+        let line = line!() + 1;
+        assert_eq!(token.try_expose(|t| t[0]), Ok(1));
+        assert_eq!(token.try_expose(|t| t[0]), Err(AccessError::AlreadyUsed));
+        let key: Limited<Protected<u8>, 1> = Limited::new(1);
+        key.try_expose(|_| ()).unwrap();
+
+        let events = collector.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, ExposureKind::Expose);
+        assert_eq!(events[0].location.file(), file!());
+        assert_eq!(events[0].location.line(), line);
+        assert!(events[0].type_name.contains("Ephemeral"));
+        assert!(events[1].type_name.contains("Limited"));
+    }
+
+    #[cfg(feature = "enclave")]
+    #[test]
+    fn test_enclave() {
+        use crate::Enclave;
+
+        let collector = AuditCollector::start();
+        let mut x: Enclave<Protected<[u8; 4]>> = Enclave::new([1; 4]);
+        x.expose(|_| ());
+        x.update(|x| x[0] = 2);
+        let _ = x.open();
+
+        let kinds: Vec<_> = collector.events().iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ExposureKind::Expose,
+                ExposureKind::Transform,
+                ExposureKind::Unwrap
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collector_stops_on_drop() {
+        let outer = AuditCollector::start();
+        {
+            let _inner = AuditCollector::start();
+            Protected::new(1u8).expose(|_| ());
+        }
+        Protected::new(1u8).expose(|_| ());
+        assert_eq!(outer.events().len(), 2);
+    }
+}
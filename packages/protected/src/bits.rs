@@ -0,0 +1,342 @@
+use crate::{slice_index::ct_in_bounds, slice_index::to_index, Protected};
+use bitvec::{order::Msb0, slice::BitSlice};
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+use subtle::{ConditionallySelectable, ConstantTimeEq as _};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// A fixed size array of `8 * N` secret bits stored in `N` bytes (most significant bit first).
+///
+/// Bits are read and written with [Protected] indices without branching on, or accessing memory at,
+/// the index: [ProtectedBits::get] and [ProtectedBits::set] scan every byte and select with a mask.
+/// Bitwise operators, [ProtectedBits::count_ones] and rotations work on whole bytes and don't depend on the bits either.
+///
+/// Use [ProtectedBits::expose] or [ProtectedBits::update] to work with the bits as a [BitSlice].
+/// The bytes are zeroized when the value is dropped.
+///
+/// # Example
+///
+/// ```
+/// use vitaminc_protected::{Controlled, Protected, ProtectedBits};
+///
+/// let mut bits = ProtectedBits::from(Protected::new(0b1000_0001u8));
+/// let first = bits.get(Protected::new(0u8)).unwrap();
+/// bits.set(Protected::new(1u8), first);
+/// bits.rotate_left(1);
+///
+/// let x: Protected<u8> = bits.into();
+/// assert_eq!(x.risky_unwrap(), 0b1000_0011);
+/// ```
+#[derive(Clone)]
+pub struct ProtectedBits<const N: usize>([u8; N]);
+
+impl<const N: usize> ProtectedBits<N> {
+    /// The number of bits.
+    pub const BITS: usize = N * 8;
+
+    /// Create a new [ProtectedBits] with all bits set to zero.
+    pub const fn zeroed() -> Self {
+        Self([0; N])
+    }
+
+    /// The number of bits.
+    pub const fn len(&self) -> usize {
+        Self::BITS
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Read the bit at `index` in constant time.
+    /// Returns `None` if `index` is out of bounds (whether the index is in bounds is not secret).
+    pub fn get<I>(&self, index: Protected<I>) -> Option<Protected<bool>>
+    where
+        I: TryInto<usize> + Zeroize,
+    {
+        let index = to_index(index.0);
+        let byte_index = index / 8;
+        let mut byte = 0u8;
+        for (i, b) in self.0.iter().enumerate() {
+            byte.conditional_assign(b, i.ct_eq(&byte_index));
+        }
+        let bit = (byte >> (7 - index % 8)) & 1;
+        ct_in_bounds(index, Self::BITS).then_some(Protected(bit == 1))
+    }
+
+    /// Write the bit at `index` in constant time.
+    /// Returns `false` (and leaves the bits unchanged) if `index` is out of bounds.
+    pub fn set<I>(&mut self, index: Protected<I>, value: Protected<bool>) -> bool
+    where
+        I: TryInto<usize> + Zeroize,
+    {
+        let index = to_index(index.0);
+        let byte_index = index / 8;
+        let mask = 0x80u8 >> (index % 8);
+        let value = (value.0 as u8).wrapping_neg() & mask;
+        for (i, b) in self.0.iter_mut().enumerate() {
+            let updated = (*b & !mask) | value;
+            b.conditional_assign(&updated, i.ct_eq(&byte_index));
+        }
+        ct_in_bounds(index, Self::BITS)
+    }
+
+    /// The number of bits set to one.
+    pub fn count_ones(&self) -> Protected<u32> {
+        Protected(self.0.iter().map(|b| b.count_ones()).sum())
+    }
+
+    /// The number of bits set to zero.
+    pub fn count_zeros(&self) -> Protected<u32> {
+        Protected(self.0.iter().map(|b| b.count_zeros()).sum())
+    }
+
+    /// Rotate the bits towards the most significant bit by `n` places (which is not secret).
+    pub fn rotate_left(&mut self, n: usize) {
+        if N == 0 {
+            return;
+        }
+        let n = n % Self::BITS;
+        self.0.rotate_left(n / 8);
+        let shift = n % 8;
+        if shift != 0 {
+            let first = self.0[0];
+            for i in 0..N - 1 {
+                self.0[i] = (self.0[i] << shift) | (self.0[i + 1] >> (8 - shift));
+            }
+            self.0[N - 1] = (self.0[N - 1] << shift) | (first >> (8 - shift));
+        }
+    }
+
+    /// Rotate the bits towards the least significant bit by `n` places (which is not secret).
+    pub fn rotate_right(&mut self, n: usize) {
+        if N == 0 {
+            return;
+        }
+        self.rotate_left(Self::BITS - n % Self::BITS);
+    }
+
+    /// Compare with another [ProtectedBits] in constant time.
+    pub fn constant_time_eq(&self, other: &Self) -> bool {
+        self.0[..].ct_eq(&other.0[..]).into()
+    }
+
+    /// Call `f` with a view of the bits.
+    pub fn expose<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&BitSlice<u8, Msb0>) -> R,
+    {
+        f(BitSlice::from_slice(&self.0))
+    }
+
+    /// Call `f` with a mutable view of the bits.
+    pub fn update<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut BitSlice<u8, Msb0>),
+    {
+        f(BitSlice::from_slice_mut(&mut self.0))
+    }
+}
+
+impl<const N: usize> Zeroize for ProtectedBits<N> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<const N: usize> Drop for ProtectedBits<N> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<const N: usize> ZeroizeOnDrop for ProtectedBits<N> {}
+
+impl<const N: usize> From<Protected<[u8; N]>> for ProtectedBits<N> {
+    fn from(bytes: Protected<[u8; N]>) -> Self {
+        Self(bytes.0)
+    }
+}
+
+impl<const N: usize> From<ProtectedBits<N>> for Protected<[u8; N]> {
+    fn from(bits: ProtectedBits<N>) -> Self {
+        Protected(bits.0)
+    }
+}
+
+macro_rules! impl_int_conversions {
+    ($($type:ty => $N:literal),+) => {
+        $(
+            impl From<Protected<$type>> for ProtectedBits<$N> {
+                fn from(x: Protected<$type>) -> Self {
+                    Self(x.0.to_be_bytes())
+                }
+            }
+
+            impl From<ProtectedBits<$N>> for Protected<$type> {
+                fn from(bits: ProtectedBits<$N>) -> Self {
+                    Protected(<$type>::from_be_bytes(bits.0))
+                }
+            }
+        )+
+    };
+}
+
+impl_int_conversions!(u8 => 1, u16 => 2, u32 => 4, u64 => 8, u128 => 16);
+
+macro_rules! impl_bit_op {
+    ($op:ident, $method:ident, $assign_op:ident, $assign_method:ident) => {
+        impl<const N: usize> $op for ProtectedBits<N> {
+            type Output = Self;
+
+            fn $method(mut self, rhs: Self) -> Self::Output {
+                self.$assign_method(rhs);
+                self
+            }
+        }
+
+        impl<const N: usize> $assign_op for ProtectedBits<N> {
+            fn $assign_method(&mut self, rhs: Self) {
+                self.0
+                    .iter_mut()
+                    .zip(rhs.0.iter())
+                    .for_each(|(x, y)| x.$assign_method(y));
+            }
+        }
+    };
+}
+
+impl_bit_op!(BitXor, bitxor, BitXorAssign, bitxor_assign);
+impl_bit_op!(BitAnd, bitand, BitAndAssign, bitand_assign);
+impl_bit_op!(BitOr, bitor, BitOrAssign, bitor_assign);
+
+impl<const N: usize> Not for ProtectedBits<N> {
+    type Output = Self;
+
+    fn not(mut self) -> Self::Output {
+        self.0.iter_mut().for_each(|x| *x = !*x);
+        self
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for ProtectedBits<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ProtectedBits<{N}> {{ ... }}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Controlled;
+    use std::format;
+
+    fn bits(x: u16) -> ProtectedBits<2> {
+        Protected::new(x).into()
+    }
+
+    fn value(bits: ProtectedBits<2>) -> u16 {
+        Protected::<u16>::from(bits).risky_unwrap()
+    }
+
+    #[test]
+    fn test_get() {
+        let x = bits(0b1010_0000_0000_0001);
+        let get = |i: u8| x.get(Protected::new(i)).map(|b| b.risky_unwrap());
+        assert_eq!(get(0), Some(true));
+        assert_eq!(get(1), Some(false));
+        assert_eq!(get(2), Some(true));
+        assert_eq!(get(15), Some(true));
+        assert_eq!(get(16), None);
+        assert!(x.get(Protected::new(usize::MAX)).is_none());
+    }
+
+    #[test]
+    fn test_set() {
+        let mut x = bits(0);
+        assert!(x.set(Protected::new(0usize), Protected::new(true)));
+        assert!(x.set(Protected::new(9u32), Protected::new(true)));
+        assert!(x.set(Protected::new(0u64), Protected::new(false)));
+        assert!(!x.set(Protected::new(16u16), Protected::new(true)));
+        assert_eq!(value(x), 0b0000_0000_0100_0000);
+    }
+
+    #[test]
+    fn test_count() {
+        let x = bits(0b1011_0000_0000_0001);
+        assert_eq!(x.count_ones().risky_unwrap(), 4);
+        assert_eq!(x.count_zeros().risky_unwrap(), 12);
+        assert_eq!(x.len(), 16);
+    }
+
+    #[test]
+    fn test_ops() {
+        assert_eq!(value(bits(0b1100) ^ bits(0b1010)), 0b0110);
+        assert_eq!(value(bits(0b1100) & bits(0b1010)), 0b1000);
+        assert_eq!(value(bits(0b1100) | bits(0b1010)), 0b1110);
+        assert_eq!(value(!bits(0b1100)), !0b1100);
+        let mut x = bits(0b1100);
+        x ^= bits(0b0110);
+        assert_eq!(value(x), 0b1010);
+    }
+
+    #[test]
+    fn test_rotate() {
+        for n in [0, 1, 7, 8, 9, 15, 16, 17, 100] {
+            let mut x = bits(0xb00f);
+            x.rotate_left(n);
+            assert_eq!(
+                value(x),
+                0xb00fu16.rotate_left(n as u32),
+                "rotate_left({n})"
+            );
+            let mut x = bits(0xb00f);
+            x.rotate_right(n);
+            assert_eq!(
+                value(x),
+                0xb00fu16.rotate_right(n as u32),
+                "rotate_right({n})"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rotate_empty() {
+        let mut x = ProtectedBits::<0>::zeroed();
+        x.rotate_left(3);
+        x.rotate_right(3);
+        assert!(x.is_empty());
+    }
+
+    #[test]
+    fn test_bytes_and_ints() {
+        let x = ProtectedBits::from(Protected::new([0x12u8, 0x34, 0x56, 0x78]));
+        let y: Protected<u32> = x.into();
+        assert_eq!(y.risky_unwrap(), 0x12345678);
+        let z = ProtectedBits::from(Protected::new(0x12345678u32));
+        assert_eq!(
+            Protected::<[u8; 4]>::from(z).risky_unwrap(),
+            [0x12, 0x34, 0x56, 0x78]
+        );
+        let x = ProtectedBits::from(Protected::new(u128::MAX - 1));
+        assert_eq!(Protected::<u128>::from(x).risky_unwrap(), u128::MAX - 1);
+    }
+
+    #[test]
+    fn test_expose_and_update() {
+        let mut x = bits(0);
+        x.update(|bits| bits.set(3, true));
+        assert!(x.expose(|bits| bits[3]));
+        assert_eq!(x.expose(|bits| bits.count_ones()), 1);
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(bits(7).constant_time_eq(&bits(7)));
+        assert!(!bits(7).constant_time_eq(&bits(6)));
+    }
+
+    #[test]
+    fn test_opaque_debug() {
+        assert_eq!(format!("{:?}", bits(1)), "ProtectedBits<2> { ... }");
+    }
+}
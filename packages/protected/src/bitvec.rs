@@ -1,23 +1,106 @@
-use crate::Protected;
+use crate::{
+    slice_index::{ct_in_bounds, ct_range_in_bounds, out_of_bounds, to_index},
+    Protected,
+};
 use bitvec::prelude::{BitOrder, BitStore};
 use bitvec::{
     ptr::{BitRef, Const, Mut},
     slice::{BitSlice, BitSliceIndex},
 };
+use core::ops::{Index, Range};
+
+macro_rules! impl_bit_slice_index {
+    ($($type:ty),+) => {
+        $(
+            impl<'a, T, O> BitSliceIndex<'a, T, O> for Protected<$type>
+            where
+                T: BitStore,
+                O: BitOrder,
+            {
+                type Immut = BitRef<'a, Const, T, O>;
+                type Mut = BitRef<'a, Mut, T, O>;
+
+                #[inline]
+                fn get(self, bits: &'a BitSlice<T, O>) -> Option<Self::Immut> {
+                    if ct_in_bounds(to_index(self.0), bits.len()) {
+                        Some(unsafe { self.get_unchecked(bits) })
+                    } else {
+                        None
+                    }
+                }
+
+                #[inline]
+                fn get_mut(self, bits: &'a mut BitSlice<T, O>) -> Option<Self::Mut> {
+                    if ct_in_bounds(to_index(self.0), bits.len()) {
+                        Some(unsafe { self.get_unchecked_mut(bits) })
+                    } else {
+                        None
+                    }
+                }
+
+                #[inline]
+                unsafe fn get_unchecked(self, bits: &'a BitSlice<T, O>) -> Self::Immut {
+                    bits.as_bitptr().add(to_index(self.0)).as_ref().unwrap()
+                }
+
+                #[inline]
+                unsafe fn get_unchecked_mut(self, bits: &'a mut BitSlice<T, O>) -> Self::Mut {
+                    bits.as_mut_bitptr().add(to_index(self.0)).as_mut().unwrap()
+                }
+
+                #[inline]
+                fn index(self, bits: &'a BitSlice<T, O>) -> Self::Immut {
+                    let len = bits.len();
+                    BitSliceIndex::get(self, bits).unwrap_or_else(|| out_of_bounds(len))
+                }
+
+                #[inline]
+                fn index_mut(self, bits: &'a mut BitSlice<T, O>) -> Self::Mut {
+                    let len = bits.len();
+                    BitSliceIndex::get_mut(self, bits).unwrap_or_else(|| out_of_bounds(len))
+                }
+            }
+
+            /// Like `BitSlice`'s `Index<usize>`, indexing can only read bits.
+            /// Use [BitSlice::get_mut] to write them.
+            /// `BitVec` forwards indexing to its `BitSlice` so this covers both.
+            ///
+            /// This is **not** constant time: it branches on the value of the bit to return a reference
+            /// to a `true` or `false` constant (and, like slice indexing, reads memory at the secret index).
+            /// Use [BitSlice::get] when the value of the bit is also secret.
+            impl<T, O> Index<Protected<$type>> for BitSlice<T, O>
+            where
+                T: BitStore,
+                O: BitOrder,
+            {
+                type Output = bool;
 
-// TODO: Implement for all Paranoid<Inner = usize> types or all types at least
-impl<'a, T, O> BitSliceIndex<'a, T, O> for Protected<usize>
+                #[inline]
+                fn index(&self, index: Protected<$type>) -> &Self::Output {
+                    if *BitSliceIndex::index(index, self) {
+                        &true
+                    } else {
+                        &false
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_bit_slice_index!(u8, u16, u32, u64, u128, usize);
+
+impl<'a, T, O> BitSliceIndex<'a, T, O> for Protected<Range<usize>>
 where
     T: BitStore,
     O: BitOrder,
 {
-    type Immut = BitRef<'a, Const, T, O>;
-    type Mut = BitRef<'a, Mut, T, O>;
+    type Immut = &'a BitSlice<T, O>;
+    type Mut = &'a mut BitSlice<T, O>;
 
     #[inline]
     fn get(self, bits: &'a BitSlice<T, O>) -> Option<Self::Immut> {
-        // TODO: This should be a constant time check?
-        if self.0 < bits.len() {
+        if ct_range_in_bounds(&self.0, bits.len()) {
             Some(unsafe { self.get_unchecked(bits) })
         } else {
             None
@@ -26,7 +109,7 @@ where
 
     #[inline]
     fn get_mut(self, bits: &'a mut BitSlice<T, O>) -> Option<Self::Mut> {
-        if self.0 < bits.len() {
+        if ct_range_in_bounds(&self.0, bits.len()) {
             Some(unsafe { self.get_unchecked_mut(bits) })
         } else {
             None
@@ -35,79 +118,90 @@ where
 
     #[inline]
     unsafe fn get_unchecked(self, bits: &'a BitSlice<T, O>) -> Self::Immut {
-        bits.as_bitptr().add(self.0).as_ref().unwrap()
+        self.0.get_unchecked(bits)
     }
 
     #[inline]
     unsafe fn get_unchecked_mut(self, bits: &'a mut BitSlice<T, O>) -> Self::Mut {
-        bits.as_mut_bitptr().add(self.0).as_mut().unwrap()
+        self.0.get_unchecked_mut(bits)
     }
 
     #[inline]
     fn index(self, bits: &'a BitSlice<T, O>) -> Self::Immut {
-        self.0
-            .get(bits)
-            .unwrap_or_else(|| panic!("index {:?} out of bounds: {}", self.0, bits.len()))
+        let len = bits.len();
+        BitSliceIndex::get(self, bits).unwrap_or_else(|| out_of_bounds(len))
     }
 
     #[inline]
     fn index_mut(self, bits: &'a mut BitSlice<T, O>) -> Self::Mut {
         let len = bits.len();
-        self.0
-            .get_mut(bits)
-            .unwrap_or_else(|| panic!("index {:?} out of bounds: {}", self.0, len))
+        BitSliceIndex::get_mut(self, bits).unwrap_or_else(|| out_of_bounds(len))
     }
 }
 
-impl<'a, T, O> BitSliceIndex<'a, T, O> for Protected<u8>
+impl<T, O> Index<Protected<Range<usize>>> for BitSlice<T, O>
 where
     T: BitStore,
     O: BitOrder,
 {
-    type Immut = BitRef<'a, Const, T, O>;
-    type Mut = BitRef<'a, Mut, T, O>;
+    type Output = BitSlice<T, O>;
 
     #[inline]
-    fn get(self, bits: &'a BitSlice<T, O>) -> Option<Self::Immut> {
-        // TODO: This should be a constant time check?
-        if (self.0 as usize) < bits.len() {
-            Some(unsafe { self.get_unchecked(bits) })
-        } else {
-            None
-        }
+    fn index(&self, index: Protected<Range<usize>>) -> &Self::Output {
+        BitSliceIndex::index(index, self)
     }
+}
 
-    #[inline]
-    fn get_mut(self, bits: &'a mut BitSlice<T, O>) -> Option<Self::Mut> {
-        if (self.0 as usize) < bits.len() {
-            Some(unsafe { self.get_unchecked_mut(bits) })
-        } else {
-            None
-        }
+#[cfg(test)]
+mod tests {
+    use crate::Protected;
+    use bitvec::{prelude::*, slice::BitSliceIndex};
+
+    #[test]
+    fn test_get() {
+        let bits = bits![u8, Lsb0; 0, 1, 0, 1];
+        assert_eq!(bits.get(Protected::new(1usize)).as_deref(), Some(&true));
+        assert_eq!(bits.get(Protected::new(2u8)).as_deref(), Some(&false));
+        assert!(bits.get(Protected::new(4usize)).is_none());
+        assert!(bits.get(Protected::new(usize::MAX)).is_none());
     }
 
-    #[inline]
-    unsafe fn get_unchecked(self, bits: &'a BitSlice<T, O>) -> Self::Immut {
-        bits.as_bitptr().add(self.0 as usize).as_ref().unwrap()
+    #[test]
+    fn test_index_mut() {
+        let bits = bits![mut u8, Lsb0; 0; 4];
+        *bits.get_mut(Protected::new(3usize)).unwrap() = true;
+        assert_eq!(bits, bits![0, 0, 0, 1]);
     }
 
-    #[inline]
-    unsafe fn get_unchecked_mut(self, bits: &'a mut BitSlice<T, O>) -> Self::Mut {
-        bits.as_mut_bitptr().add(self.0 as usize).as_mut().unwrap()
+    #[test]
+    #[should_panic(expected = "protected index out of bounds: 4")]
+    fn test_index_out_of_bounds() {
+        let bits = bits![u8, Lsb0; 0; 4];
+        let _ = Protected::new(4u8).index(bits);
     }
 
-    #[inline]
-    fn index(self, bits: &'a BitSlice<T, O>) -> Self::Immut {
-        (self.0 as usize)
-            .get(bits)
-            .unwrap_or_else(|| panic!("index {:?} out of bounds: {}", self.0, bits.len()))
+    #[test]
+    fn test_index_widths() {
+        let mut bits: BitVec<u16, Msb0> = bitvec![u16, Msb0; 0; 20];
+        bits.set(17, true);
+        assert!(bits[Protected::new(17u8)]);
+        assert!(bits[Protected::new(17u16)]);
+        assert!(!bits[Protected::new(16u32)]);
+        assert!(bits[Protected::new(17u64)]);
+        assert!(bits[Protected::new(17u128)]);
+        *bits.get_mut(Protected::new(0u32)).unwrap() = true;
+        assert!(bits.as_bitslice()[Protected::new(0usize)]);
     }
 
-    #[inline]
-    fn index_mut(self, bits: &'a mut BitSlice<T, O>) -> Self::Mut {
-        let len = bits.len();
-        (self.0 as usize)
-            .get_mut(bits)
-            .unwrap_or_else(|| panic!("index {:?} out of bounds: {}", self.0, len))
+    #[test]
+    fn test_index_range() {
+        let mut bits = bitvec![u8, Lsb0; 0, 1, 1, 0];
+        let range = || Protected::range(Protected::new(1), Protected::new(3));
+        assert_eq!(&bits[range()], bits![1, 1]);
+        bits.get_mut(range()).unwrap().fill(false);
+        assert!(bits.not_any());
+        assert!(bits
+            .get(Protected::range(Protected::new(2), Protected::new(5)))
+            .is_none());
     }
 }
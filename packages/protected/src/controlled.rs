@@ -1,5 +1,8 @@
 pub use crate::Protected;
-use crate::{private::ControlledPrivate, AsProtectedRef, ProtectedRef, ReplaceT};
+use crate::{
+    private::{ControlledPrivate, InnerRef},
+    AsProtectedRef, ProtectedRef, ReplaceT,
+};
 use zeroize::Zeroize;
 
 pub trait Controlled: ControlledPrivate {
@@ -74,6 +77,7 @@ pub trait Controlled: ControlledPrivate {
     /// let y = x.map(|x| x + 10);
     /// assert_eq!(y.risky_unwrap(), 110);
     /// ```
+    #[cfg_attr(feature = "audit", track_caller)]
     fn map<B, F>(self, f: F) -> <Self as ReplaceT<B>>::Output
     where
         Self: Sized + ReplaceT<B>,
@@ -81,7 +85,9 @@ pub trait Controlled: ControlledPrivate {
         <Self as ReplaceT<B>>::Output: ControlledPrivate<Inner = B>,
         B: Zeroize,
     {
-        <Self as ReplaceT<B>>::Output::init_from_inner(f(self.risky_unwrap()))
+        #[cfg(feature = "audit")]
+        crate::audit::record::<Self>(crate::audit::ExposureKind::Transform);
+        <Self as ReplaceT<B>>::Output::init_from_inner(f(self.into_inner()))
     }
 
     /// Zip two [Controlled] values of the same type together with a function that combines them.
@@ -100,6 +106,7 @@ pub trait Controlled: ControlledPrivate {
     ///
     /// TODO: Apply Usage trait bounds to prevent accidental broadening of scope
     /// e.g. `other` must have the same, or broader scope as `self`
+    #[cfg_attr(feature = "audit", track_caller)]
     fn zip<Other, Out, F>(self, b: Other, f: F) -> Protected<Out>
     where
         Self: Sized,
@@ -107,8 +114,12 @@ pub trait Controlled: ControlledPrivate {
         Out: Zeroize,
         F: FnOnce(Self::Inner, Other::Inner) -> Out,
     {
+        #[cfg(feature = "audit")]
+        crate::audit::record::<Self>(crate::audit::ExposureKind::Transform);
+        #[cfg(feature = "audit")]
+        crate::audit::record::<Other>(crate::audit::ExposureKind::Transform);
         // TODO: Use Replace private trait
-        Protected::init_from_inner(f(self.risky_unwrap(), b.risky_unwrap()))
+        Protected::init_from_inner(f(self.into_inner(), b.into_inner()))
     }
 
     /// Like `zip` but the second argument is a reference.
@@ -123,6 +134,7 @@ pub trait Controlled: ControlledPrivate {
     /// assert_eq!(z.risky_unwrap(), "hello world");
     /// ```
     ///
+    #[cfg_attr(feature = "audit", track_caller)]
     fn zip_ref<'a, A, Other, Out, F>(self, other: &'a Other, f: F) -> Protected<Out>
     where
         Self: Sized,
@@ -131,8 +143,10 @@ pub trait Controlled: ControlledPrivate {
         Out: Zeroize,
         F: FnOnce(Self::Inner, &A) -> Out,
     {
+        #[cfg(feature = "audit")]
+        crate::audit::record::<Self>(crate::audit::ExposureKind::Transform);
         let arg: ProtectedRef<'a, A> = other.as_protected_ref();
-        Protected::init_from_inner(f(self.risky_unwrap(), arg.inner_ref()))
+        Protected::init_from_inner(f(self.into_inner(), arg.inner_ref()))
     }
 
     /// Similar to `map` but using references to that the inner value is updated in place.
@@ -150,11 +164,14 @@ pub trait Controlled: ControlledPrivate {
     /// assert_eq!(x.risky_unwrap(), [1, 1, 1, 1]);
     /// ```
     ///
+    #[cfg_attr(feature = "audit", track_caller)]
     fn update<F>(&mut self, mut f: F)
     where
         F: FnMut(&mut Self::Inner),
     {
-        f(self.inner_mut());
+        #[cfg(feature = "audit")]
+        crate::audit::record::<Self>(crate::audit::ExposureKind::Transform);
+        self.with_inner_mut(|x| f(x));
     }
 
     /// Update the inner value with another [Controlled] value.
@@ -174,6 +191,7 @@ pub trait Controlled: ControlledPrivate {
     ///
     /// TODO: Apply Usage trait bounds to prevent accidental broadening of scope
     /// e.g. `other` must have the same, or broader scope as `self`
+    #[cfg_attr(feature = "audit", track_caller)]
     fn update_with<Other, F>(&mut self, other: Other, mut f: F)
     where
         F: FnMut(&mut Self::Inner, Other::Inner),
@@ -183,7 +201,12 @@ pub trait Controlled: ControlledPrivate {
         // But not all Zeroize types are ZeroizeOnDrop - we may need to yield a wrapper type that Derefs to the inner value
         // Ditto for the zip method
         // Either that or just make sure the caller uses zeroize() on the other value :/
-        f(self.inner_mut(), other.risky_unwrap());
+        #[cfg(feature = "audit")]
+        crate::audit::record::<Self>(crate::audit::ExposureKind::Transform);
+        #[cfg(feature = "audit")]
+        crate::audit::record::<Other>(crate::audit::ExposureKind::Transform);
+        let other = other.into_inner();
+        self.with_inner_mut(|x| f(x, other));
     }
 
     /// Like `update_with` but the second argument is a reference.
@@ -202,18 +225,31 @@ pub trait Controlled: ControlledPrivate {
     /// assert_eq!(x.risky_unwrap(), [1u8; 32]);
     /// ```
     ///
+    #[cfg_attr(feature = "audit", track_caller)]
     fn update_with_ref<'a, A, F>(&mut self, other: ProtectedRef<'a, A>, mut f: F)
     where
         A: ?Sized + 'a,
         F: FnMut(&mut Self::Inner, &A),
     {
-        f(self.inner_mut(), other.inner_ref());
+        #[cfg(feature = "audit")]
+        crate::audit::record::<Self>(crate::audit::ExposureKind::Transform);
+        self.with_inner_mut(|x| f(x, other.inner_ref()));
     }
 
     /// Iterate over the inner value and wrap each element in a `Protected`.
     /// `I` must be `Copy` because [Protected] always takes ownership of the inner value.
+    ///
+    /// Not available for types that only lend their inner value to a closure (e.g. [crate::Masked]).
+    ///
+    /// ```compile_fail
+    /// use vitaminc_protected::{Controlled, Masked};
+    ///
+    /// let key = Masked::new([1u8; 32]);
+    /// let bytes: Vec<_> = key.iter().collect();
+    /// ```
     fn iter<'a, I>(&'a self) -> impl Iterator<Item = Protected<I>>
     where
+        Self: InnerRef,
         <Self as ControlledPrivate>::Inner: AsRef<[I]>,
         I: Copy + 'a,
     {
@@ -237,7 +273,70 @@ pub trait Controlled: ControlledPrivate {
     where
         Self: Sized,
     {
-        Self::init_from_inner(std::mem::replace(self.inner_mut(), new.risky_unwrap()))
+        let new = new.into_inner();
+        Self::init_from_inner(self.with_inner_mut(|x| core::mem::replace(x, new)))
+    }
+
+    /// Expose a reference to the inner value to a closure.
+    /// Prefer this to [Controlled::risky_unwrap] when passing a secret to a function
+    /// that takes a reference (e.g. `&[u8]`).
+    ///
+    /// The result type `R` is chosen outside of the closure so it cannot borrow the inner value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vitaminc_protected::{Controlled, Protected};
+    ///
+    /// fn third_party_len(bytes: &[u8]) -> usize {
+    ///     bytes.len()
+    /// }
+    ///
+    /// let x = Protected::new([1u8; 32]);
+    /// assert_eq!(x.expose(|bytes| third_party_len(bytes)), 32);
+    /// ```
+    ///
+    /// Returning a reference to the inner value does not compile.
+    ///
+    /// ```compile_fail
+    /// use vitaminc_protected::{Controlled, Protected};
+    ///
+    /// let x = Protected::new([1u8; 32]);
+    /// let leaked: &[u8; 32] = x.expose(|bytes| bytes);
+    /// ```
+    #[cfg_attr(feature = "audit", track_caller)]
+    fn expose<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&Self::Inner) -> R,
+    {
+        #[cfg(feature = "audit")]
+        crate::audit::record::<Self>(crate::audit::ExposureKind::Expose);
+        self.with_inner(f)
+    }
+
+    /// Like [Controlled::expose] but with a mutable reference to the inner value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vitaminc_protected::{Controlled, Protected};
+    ///
+    /// fn third_party_fill(bytes: &mut [u8]) -> usize {
+    ///     bytes.fill(7);
+    ///     bytes.len()
+    /// }
+    ///
+    /// let mut x = Protected::new([0u8; 4]);
+    /// assert_eq!(x.expose_mut(|bytes| third_party_fill(bytes)), 4);
+    /// ```
+    #[cfg_attr(feature = "audit", track_caller)]
+    fn expose_mut<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Self::Inner) -> R,
+    {
+        #[cfg(feature = "audit")]
+        crate::audit::record::<Self>(crate::audit::ExposureKind::Expose);
+        self.with_inner_mut(f)
     }
 
     /// Unwraps the inner value of the [Controlled] type.
@@ -246,8 +345,18 @@ pub trait Controlled: ControlledPrivate {
     ///
     /// **Use with caution!**
     ///
-    // TODO: Consider feature flagging this method
-    fn risky_unwrap(self) -> Self::Inner;
+    /// Enable the `forbid-risky-unwrap` feature to remove this method (outside of this crate's own tests)
+    /// and use [Controlled::expose] or [Controlled::expose_mut] instead.
+    #[cfg(any(test, not(feature = "forbid-risky-unwrap")))]
+    #[cfg_attr(feature = "audit", track_caller)]
+    fn risky_unwrap(self) -> Self::Inner
+    where
+        Self: Sized,
+    {
+        #[cfg(feature = "audit")]
+        crate::audit::record::<Self>(crate::audit::ExposureKind::RiskyUnwrap);
+        self.into_inner()
+    }
 }
 
 // TODO: Implement Collect for Protected (or Paranoid) so we can use collect() on iterators
@@ -9,7 +9,8 @@ impl<T: Zeroize> From<T> for Protected<T> {
     }
 }
 
-impl<const N: usize> From<[char; N]> for Protected<String> {
+#[cfg(feature = "alloc")]
+impl<const N: usize> From<[char; N]> for Protected<alloc::string::String> {
     fn from(x: [char; N]) -> Self {
         Self::new(x.iter().collect())
     }
@@ -30,29 +31,59 @@ mod tests {
     use super::*;
     use crate::Controlled;
     use digest::consts::U48;
-    use std::num::NonZeroU8;
+    use proptest::prelude::*;
+    use std::{format, num::NonZeroU8, string::String};
 
-    macro_rules! test_integer_into_protected {
-        ($($t:ty)*) => ($(
-            // TODO: Use proptests or kani proof
-            let x: $t = 0;
-            let y: Protected<_> = x.into();
-            assert_eq!(y.risky_unwrap(), x);
-        )*)
+    macro_rules! test_into_protected {
+        ($($name:ident: $t:ty),*) => {
+            proptest! {
+                $(
+                    #[test]
+                    fn $name(x in any::<$t>()) {
+                        let y: Protected<_> = x.clone().into();
+                        prop_assert_eq!(y.risky_unwrap(), x);
+                    }
+                )*
+            }
+        };
     }
 
+    test_into_protected!(
+        test_u8_into_protected: u8,
+        test_u16_into_protected: u16,
+        test_u32_into_protected: u32,
+        test_u64_into_protected: u64,
+        test_u128_into_protected: u128,
+        test_usize_into_protected: usize,
+        test_i8_into_protected: i8,
+        test_i16_into_protected: i16,
+        test_i32_into_protected: i32,
+        test_i64_into_protected: i64,
+        test_i128_into_protected: i128,
+        test_isize_into_protected: isize,
+        test_u8_array_into_protected: [u8; 1],
+        test_u8_array_32_into_protected: [u8; 32],
+        test_u16_array_into_protected: [u16; 17],
+        test_u32_array_into_protected: [u32; 8],
+        test_u64_array_into_protected: [u64; 4],
+        test_u128_array_into_protected: [u128; 3],
+        test_i8_array_into_protected: [i8; 32],
+        test_i32_array_into_protected: [i32; 7],
+        test_i64_array_into_protected: [i64; 2],
+        test_non_zero_into_protected: NonZeroU8,
+        test_string_into_protected: String
+    );
+
     macro_rules! test_array_into_protected {
         ($t:ty; $($size:expr),*) => ($(
-            // TODO: Use proptests or kani proof
-            let x: [$t; $size] = [0; $size];
+            let x: [$t; $size] = core::array::from_fn(|i| i as $t);
             let y: Protected<_> = x.into();
             assert_eq!(y.risky_unwrap(), x);
         )*);
     }
 
     #[test]
-    fn test_into_protected() {
-        test_integer_into_protected!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+    fn test_array_sizes_into_protected() {
         test_array_into_protected!(u8; 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64);
         test_array_into_protected!(u16; 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64);
         test_array_into_protected!(u32; 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64);
@@ -67,20 +98,6 @@ mod tests {
         test_array_into_protected!(isize; 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64);
     }
 
-    #[test]
-    fn test_non_zero_into_protected() {
-        let x: NonZeroU8 = NonZeroU8::new(1).unwrap();
-        let y: Protected<_> = x.into();
-        assert_eq!(y.risky_unwrap(), x);
-    }
-
-    #[test]
-    fn test_string_into_protected() {
-        let x: String = "hello".into();
-        let y: Protected<_> = x.into();
-        assert_eq!(&y.risky_unwrap(), "hello");
-    }
-
     #[test]
     fn test_char_array_into_string() {
         let x: [char; 10] = ['c', 'o', 'o', 'k', 'i', 'e', 's', '!', '!', '!'];
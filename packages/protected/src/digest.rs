@@ -1,4 +1,4 @@
-use std::marker::PhantomData;
+use core::marker::PhantomData;
 
 use crate::{Acceptable, Controlled, DefaultScope, Scope};
 use digest::generic_array::GenericArray;
@@ -8,7 +8,7 @@ use digest::Output;
 use digest::OutputSizeUser;
 use digest::Reset;
 
-pub struct ProtectedDigest<D, InputScope = DefaultScope>(D, PhantomData<InputScope>);
+pub struct ProtectedDigest<D, InputScope = DefaultScope>(pub(crate) D, PhantomData<InputScope>);
 
 // TODO: Implement Usage scopes
 // TODO: How can we force that the Digest types have Zeroize enabled? (Its a feature in the digest crate but in the 0.11.0.pre versions)
@@ -22,7 +22,10 @@ impl<D: Digest, InputScope: Scope> ProtectedDigest<D, InputScope> {
         T: Controlled + Acceptable<InputScope>,
         T::Inner: AsRef<[u8]>,
     {
-        Self(D::new_with_prefix(data.inner()), PhantomData)
+        Self(
+            data.with_inner(|data| D::new_with_prefix(data)),
+            PhantomData,
+        )
     }
 
     pub fn update<T>(&mut self, data: &T)
@@ -30,7 +33,7 @@ impl<D: Digest, InputScope: Scope> ProtectedDigest<D, InputScope> {
         T: Controlled + Acceptable<InputScope>,
         T::Inner: AsRef<[u8]>,
     {
-        self.0.update(data.inner())
+        data.with_inner(|data| self.0.update(data))
     }
 
     pub fn finalize<T>(self) -> T
@@ -41,13 +44,15 @@ impl<D: Digest, InputScope: Scope> ProtectedDigest<D, InputScope> {
         result.into()
     }
 
-    pub fn finalize_into<'m, T>(self, out: &'m mut T)
+    pub fn finalize_into<T>(self, out: &mut T)
     where
         T: Controlled,
-        &'m mut GenericArray<u8, <D as OutputSizeUser>::OutputSize>: From<&'m mut T::Inner>,
+        for<'m> &'m mut GenericArray<u8, <D as OutputSizeUser>::OutputSize>: From<&'m mut T::Inner>,
     {
-        let target: &mut Output<D> = out.inner_mut().into();
-        self.0.finalize_into(target);
+        out.with_inner_mut(|out| {
+            let target: &mut Output<D> = out.into();
+            self.0.finalize_into(target);
+        })
     }
 
     pub fn finalize_reset<T>(&mut self) -> T
@@ -59,14 +64,16 @@ impl<D: Digest, InputScope: Scope> ProtectedDigest<D, InputScope> {
         result.into()
     }
 
-    pub fn finalize_into_reset<'m, T>(&'m mut self, out: &'m mut T)
+    pub fn finalize_into_reset<T>(&mut self, out: &mut T)
     where
         D: FixedOutputReset,
         T: Controlled,
-        &'m mut GenericArray<u8, <D as OutputSizeUser>::OutputSize>: From<&'m mut T::Inner>,
+        for<'m> &'m mut GenericArray<u8, <D as OutputSizeUser>::OutputSize>: From<&'m mut T::Inner>,
     {
-        let target: &mut Output<D> = out.inner_mut().into();
-        Digest::finalize_into_reset(&mut self.0, target);
+        out.with_inner_mut(|out| {
+            let target: &mut Output<D> = out.into();
+            Digest::finalize_into_reset(&mut self.0, target);
+        })
     }
 
     pub fn reset(&mut self)
@@ -0,0 +1,199 @@
+use crate::{private::ControlledPrivate, Controlled};
+use chacha20poly1305::{
+    aead::{AeadCore, AeadInPlace, KeyInit, OsRng},
+    Key, Tag, XChaCha20Poly1305, XNonce,
+};
+use std::{marker::PhantomData, sync::OnceLock};
+use zeroize::{Zeroize, Zeroizing};
+
+const AAD: &[u8] = b"vitaminc.enclave.v1";
+
+/// Key generated on first use and never leaves this process.
+static ENCLAVE_KEY: OnceLock<Zeroizing<[u8; 32]>> = OnceLock::new();
+
+fn cipher() -> XChaCha20Poly1305 {
+    let key = ENCLAVE_KEY
+        .get_or_init(|| Zeroizing::new(XChaCha20Poly1305::generate_key(&mut OsRng).into()));
+    XChaCha20Poly1305::new(Key::from_slice(key.as_ref()))
+}
+
+/// A wrapper that keeps a [Controlled] value encrypted in memory while it is not being used
+/// (similar to the `Enclave` type in Go's memguard).
+///
+/// The inner value is encrypted with XChaCha20-Poly1305 under an ephemeral key that is generated
+/// the first time an [Enclave] is created and is never exported.
+/// It is only decrypted, into a temporary buffer that is zeroized afterwards (even if the closure panics),
+/// for the duration of an [Enclave::expose] or [Enclave::update] call,
+/// so memory dumps and cold-boot snapshots taken while a key is idle only contain ciphertext.
+///
+/// This protects long-lived keys from snapshots of memory, not from an attacker that can read the memory
+/// of a running process at will (the enclave key is itself held in memory).
+///
+/// [Enclave] is not [Controlled] itself because there is no plaintext value to borrow.
+/// Use [Enclave::open] to decrypt it back into a value of type `T`.
+///
+/// # Example
+///
+/// ```
+/// use vitaminc_protected::{Controlled, Enclave, Protected};
+///
+/// let mut master_key: Enclave<Protected<[u8; 32]>> = Enclave::new([42; 32]);
+/// assert_eq!(master_key.expose(|k| k[0]), 42);
+///
+/// // Modifications are re-encrypted with a fresh nonce
+/// master_key.update(|k| k[0] = 1);
+/// let key: Protected<[u8; 32]> = master_key.open();
+/// assert_eq!(key.risky_unwrap()[0], 1);
+/// ```
+pub struct Enclave<T: ControlledPrivate> {
+    ciphertext: T::Inner,
+    nonce: XNonce,
+    tag: Tag,
+    _type: PhantomData<T>,
+}
+
+impl<T> Enclave<T>
+where
+    T: Controlled,
+    T::Inner: AsMut<[u8]> + Clone + Zeroize,
+{
+    /// Encrypt an inner value into a new [Enclave].
+    pub fn new(x: T::Inner) -> Self {
+        Self::seal(Zeroizing::new(x))
+    }
+
+    /// Move an existing [Controlled] value into a new [Enclave].
+    pub fn from_controlled(x: T) -> Self {
+        Self::new(x.into_inner())
+    }
+
+    /// Decrypt the inner value and pass a reference to it to a closure.
+    /// The decrypted copy is zeroized when the closure returns.
+    #[cfg_attr(feature = "audit", track_caller)]
+    pub fn expose<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&T::Inner) -> R,
+    {
+        #[cfg(feature = "audit")]
+        crate::audit::record::<T>(crate::audit::ExposureKind::Expose);
+        f(&self.decrypt())
+    }
+
+    /// Decrypt the inner value, update it in place and encrypt it again with a new nonce.
+    #[cfg_attr(feature = "audit", track_caller)]
+    pub fn update<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut T::Inner),
+    {
+        #[cfg(feature = "audit")]
+        crate::audit::record::<T>(crate::audit::ExposureKind::Transform);
+        let mut plaintext = self.decrypt();
+        f(&mut plaintext);
+        *self = Self::seal(plaintext);
+    }
+
+    /// Decrypt the enclave into a value of type `T`.
+    #[cfg_attr(feature = "audit", track_caller)]
+    pub fn open(&self) -> T {
+        #[cfg(feature = "audit")]
+        crate::audit::record::<T>(crate::audit::ExposureKind::Unwrap);
+        // Swap the ciphertext into the zeroizing buffer so the plaintext is only held by `T`
+        let mut plaintext = self.decrypt();
+        T::init_from_inner(std::mem::replace(&mut *plaintext, self.ciphertext.clone()))
+    }
+
+    fn seal(mut plaintext: Zeroizing<T::Inner>) -> Self {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let tag = cipher()
+            .encrypt_in_place_detached(&nonce, AAD, plaintext.as_mut())
+            .expect("Enclave encryption failed");
+
+        // `plaintext` now holds the ciphertext
+        Self {
+            ciphertext: T::Inner::clone(&plaintext),
+            nonce,
+            tag,
+            _type: PhantomData,
+        }
+    }
+
+    fn decrypt(&self) -> Zeroizing<T::Inner> {
+        let mut buffer = Zeroizing::new(self.ciphertext.clone());
+        cipher()
+            .decrypt_in_place_detached(&self.nonce, AAD, buffer.as_mut(), &self.tag)
+            .expect("Enclave decryption failed (memory is corrupted)");
+        buffer
+    }
+}
+
+impl<T> Clone for Enclave<T>
+where
+    T: ControlledPrivate,
+    T::Inner: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            ciphertext: self.ciphertext.clone(),
+            nonce: self.nonce,
+            tag: self.tag,
+            _type: PhantomData,
+        }
+    }
+}
+
+/// Only the ciphertext is ever held so there is nothing to redact, but it isn't printed either.
+impl<T: ControlledPrivate> std::fmt::Debug for Enclave<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Enclave").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Equatable, Protected};
+
+    #[test]
+    fn test_ciphertext_differs_from_plaintext() {
+        let x: Enclave<Protected<[u8; 32]>> = Enclave::new([42; 32]);
+        assert_ne!(x.ciphertext, [42; 32]);
+        assert_eq!(x.expose(|k| *k), [42; 32]);
+    }
+
+    #[test]
+    fn test_vec() {
+        let x: Enclave<Protected<Vec<u8>>> = Enclave::new(b"hunter2".to_vec());
+        assert_ne!(x.ciphertext, b"hunter2");
+        assert_eq!(x.open().risky_unwrap(), b"hunter2");
+    }
+
+    #[test]
+    fn test_update_uses_new_nonce() {
+        let mut x: Enclave<Protected<[u8; 4]>> = Enclave::new([1; 4]);
+        let nonce = x.nonce;
+        x.update(|k| k[0] = 2);
+        assert_ne!(x.nonce, nonce);
+        assert_eq!(x.expose(|k| *k), [2, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_from_controlled() {
+        type Key = Equatable<Protected<[u8; 4]>>;
+        let x = Enclave::from_controlled(Key::new([1; 4]));
+        assert_eq!(x.open(), Key::new([1; 4]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Enclave decryption failed")]
+    fn test_tampered() {
+        let mut x: Enclave<Protected<[u8; 4]>> = Enclave::new([1; 4]);
+        x.ciphertext[0] ^= 1;
+        x.expose(|_| ());
+    }
+
+    #[test]
+    fn test_debug() {
+        let x: Enclave<Protected<[u8; 4]>> = Enclave::new([1; 4]);
+        assert_eq!(format!("{x:?}"), "Enclave { .. }");
+    }
+}
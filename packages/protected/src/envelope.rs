@@ -0,0 +1,610 @@
+//! Self-describing envelope encoding for exported secrets.
+//!
+//! Raw exported bytes carry no information about what they contain so a 32-byte MAC key
+//! can be happily loaded as a 32-byte permutation key.
+//! An envelope prefixes the secret with a header that records a magic value, the format version,
+//! a type identifier and the payload length so that loading a secret into the wrong type fails.
+//!
+//! # Binary format
+//!
+//! | Field        | Size          | Notes                              |
+//! |--------------|---------------|------------------------------------|
+//! | Magic        | 4             | `VTMC`                             |
+//! | Version      | 1             | Currently `1`                      |
+//! | Type ID len  | 1             |                                    |
+//! | Type ID      | Type ID len   | ASCII, see [TypeIdentifier]        |
+//! | Payload len  | 4             | Big endian, at most `u32::MAX`     |
+//! | Payload      | Payload len   |                                    |
+//!
+//! # Text format
+//!
+//! `vitaminc:<version>:<type id>:<payload len>:<lowercase hex payload>`
+//!
+//! Payloads in both formats are limited to `u32::MAX` bytes.
+//!
+//! # Example
+//!
+//! ```
+//! use vitaminc_protected::{Controlled, Envelope, Exportable, Protected, Scope, TypeIdentifier, Usage};
+//!
+//! struct MacKeyScope;
+//! impl Scope for MacKeyScope {}
+//! impl TypeIdentifier for MacKeyScope {
+//!     const TYPE_ID: &'static str = "example.mac-key";
+//! }
+//!
+//! type MacKey = Usage<Exportable<Protected<[u8; 32]>>, MacKeyScope>;
+//!
+//! let key = MacKey::new([7; 32]);
+//! let text = key.to_envelope_string().unwrap();
+//! let loaded = MacKey::from_envelope_str(text.risky_unwrap().as_str()).unwrap();
+//! assert_eq!(loaded.risky_unwrap(), [7; 32]);
+//! ```
+use crate::{
+    private::{ControlledPrivate, Exported},
+    Controlled, Protected, Scope, Usage,
+};
+use alloc::{format, string::String, vec::Vec};
+use core::fmt;
+use zeroize::Zeroize;
+
+const MAGIC: [u8; 4] = *b"VTMC";
+const TEXT_PREFIX: &str = "vitaminc";
+
+/// The envelope format version written by this crate.
+pub const ENVELOPE_VERSION: u8 = 1;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EnvelopeError {
+    InvalidMagic,
+    UnsupportedVersion(u8),
+    TypeMismatch {
+        expected: &'static str,
+        found: String,
+    },
+    LengthMismatch {
+        expected: usize,
+        found: usize,
+    },
+    Truncated,
+    TrailingData,
+    InvalidEncoding,
+    InvalidPayload,
+    PayloadTooLarge(usize),
+}
+
+// `Display` is implemented by hand because `thiserror` needs `std`
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvelopeError::InvalidMagic => f.write_str("Envelope is missing the expected header"),
+            EnvelopeError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported envelope version {version}")
+            }
+            EnvelopeError::TypeMismatch { expected, found } => {
+                write!(
+                    f,
+                    "Envelope contains `{found}` but `{expected}` was expected"
+                )
+            }
+            EnvelopeError::LengthMismatch { expected, found } => write!(
+                f,
+                "Envelope payload is {found} bytes but {expected} bytes were expected"
+            ),
+            EnvelopeError::Truncated => f.write_str("Envelope is truncated"),
+            EnvelopeError::TrailingData => f.write_str("Envelope has trailing data"),
+            EnvelopeError::InvalidEncoding => f.write_str("Envelope is not correctly encoded"),
+            EnvelopeError::InvalidPayload => {
+                f.write_str("Envelope payload is not valid for the target type")
+            }
+            EnvelopeError::PayloadTooLarge(len) => write!(
+                f,
+                "Envelope payload is {len} bytes but at most 4GiB is supported"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EnvelopeError {}
+
+/// Identifies the type (or purpose) of a secret stored in an envelope.
+///
+/// This is typically implemented on a [Scope] so that [Usage] types carry their identifier
+/// at compile time. Identifiers must be between 1 and 255 printable ASCII characters and
+/// must not contain a `:`. Invalid identifiers are rejected at compile time.
+pub trait TypeIdentifier {
+    const TYPE_ID: &'static str;
+}
+
+/// Only exportable types can be written to an envelope.
+impl<T, S> TypeIdentifier for Usage<T, S>
+where
+    T: Exported,
+    S: Scope + TypeIdentifier,
+{
+    const TYPE_ID: &'static str = S::TYPE_ID;
+}
+
+/// Inner types that can be stored as the payload of an envelope.
+pub trait EnvelopePayload: Sized {
+    /// The exact payload length for fixed size types.
+    fn expected_len() -> Option<usize> {
+        None
+    }
+
+    fn payload_bytes(&self) -> &[u8];
+
+    fn from_payload_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+impl<const N: usize> EnvelopePayload for [u8; N] {
+    fn expected_len() -> Option<usize> {
+        Some(N)
+    }
+
+    fn payload_bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn from_payload_bytes(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok()
+    }
+}
+
+impl EnvelopePayload for Vec<u8> {
+    fn payload_bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn from_payload_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(bytes.to_vec())
+    }
+}
+
+impl EnvelopePayload for String {
+    fn payload_bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+
+    fn from_payload_bytes(bytes: &[u8]) -> Option<Self> {
+        // Validate first so that a failed conversion doesn't leave a copy behind
+        core::str::from_utf8(bytes).ok().map(String::from)
+    }
+}
+
+/// Encode and decode [Controlled] values as self-describing envelopes.
+///
+/// Implemented for every [Controlled] type with a [TypeIdentifier] and an [EnvelopePayload] inner type.
+pub trait Envelope: Sized {
+    /// Encode as a binary envelope.
+    /// Fails if the payload is larger than `u32::MAX` bytes.
+    fn to_envelope(&self) -> Result<Protected<Vec<u8>>, EnvelopeError>;
+
+    /// Encode as a text envelope.
+    /// Fails if the payload is larger than `u32::MAX` bytes.
+    fn to_envelope_string(&self) -> Result<Protected<String>, EnvelopeError>;
+
+    /// Decode a binary envelope, checking the header matches `Self`.
+    fn from_envelope(bytes: &[u8]) -> Result<Self, EnvelopeError>;
+
+    /// Decode a text envelope, checking the header matches `Self`.
+    fn from_envelope_str(text: &str) -> Result<Self, EnvelopeError>;
+}
+
+impl<T> Envelope for T
+where
+    T: Controlled + TypeIdentifier,
+    T::Inner: EnvelopePayload,
+{
+    #[cfg_attr(feature = "audit", track_caller)]
+    fn to_envelope(&self) -> Result<Protected<Vec<u8>>, EnvelopeError> {
+        #[cfg(feature = "audit")]
+        crate::audit::record::<T>(crate::audit::ExposureKind::Export);
+        let type_id = valid_type_id::<T>();
+        self.with_inner(|inner| {
+            let payload = inner.payload_bytes();
+            let payload_len = check_payload_len(payload.len())?;
+            Ok(Protected::generate(|| {
+                // Allocate the exact size up front so the buffer is never reallocated (and copied)
+                let mut out = Vec::with_capacity(header_len(type_id) + payload.len());
+                write_header(&mut out, type_id, payload_len);
+                out.extend_from_slice(payload);
+                out
+            }))
+        })
+    }
+
+    #[cfg_attr(feature = "audit", track_caller)]
+    fn to_envelope_string(&self) -> Result<Protected<String>, EnvelopeError> {
+        #[cfg(feature = "audit")]
+        crate::audit::record::<T>(crate::audit::ExposureKind::Export);
+        let type_id = valid_type_id::<T>();
+        self.with_inner(|inner| {
+            let payload = inner.payload_bytes();
+            let payload_len = check_payload_len(payload.len())?;
+            let prefix = format!("{TEXT_PREFIX}:{ENVELOPE_VERSION}:{type_id}:{payload_len}:");
+            Ok(Protected::generate(|| {
+                let mut out = String::with_capacity(prefix.len() + payload.len() * 2);
+                out.push_str(&prefix);
+                payload.iter().for_each(|byte| {
+                    out.push(encode_nibble(byte >> 4));
+                    out.push(encode_nibble(byte & 0x0f));
+                });
+                out
+            }))
+        })
+    }
+
+    fn from_envelope(bytes: &[u8]) -> Result<Self, EnvelopeError> {
+        let type_id = valid_type_id::<T>();
+        let (magic, rest) = split(bytes, MAGIC.len())?;
+        if magic != MAGIC {
+            return Err(EnvelopeError::InvalidMagic);
+        }
+        let (version, rest) = split(rest, 1)?;
+        check_version(version[0])?;
+        let (id_len, rest) = split(rest, 1)?;
+        let (found_id, rest) = split(rest, id_len[0] as usize)?;
+        check_type_id(type_id, found_id)?;
+        let (payload_len, payload) = split(rest, 4)?;
+        let payload_len = u32::from_be_bytes(payload_len.try_into().expect("4 bytes")) as usize;
+        check_len::<T::Inner>(payload_len)?;
+        match payload.len() {
+            n if n < payload_len => Err(EnvelopeError::Truncated),
+            n if n > payload_len => Err(EnvelopeError::TrailingData),
+            _ => T::Inner::from_payload_bytes(payload)
+                .map(T::init_from_inner)
+                .ok_or(EnvelopeError::InvalidPayload),
+        }
+    }
+
+    fn from_envelope_str(text: &str) -> Result<Self, EnvelopeError> {
+        let type_id = valid_type_id::<T>();
+        let mut parts = text.splitn(5, ':');
+        let mut next = || parts.next().ok_or(EnvelopeError::Truncated);
+        if next()? != TEXT_PREFIX {
+            return Err(EnvelopeError::InvalidMagic);
+        }
+        let version = next()?
+            .parse::<u8>()
+            .map_err(|_| EnvelopeError::InvalidEncoding)?;
+        check_version(version)?;
+        check_type_id(type_id, next()?.as_bytes())?;
+        // Bounded like the binary format so that the hex length can't overflow
+        let payload_len = next()?
+            .parse::<u32>()
+            .map_err(|_| EnvelopeError::InvalidEncoding)? as usize;
+        check_len::<T::Inner>(payload_len)?;
+        let hex_len = payload_len
+            .checked_mul(2)
+            .ok_or(EnvelopeError::InvalidEncoding)?;
+        let hex = next()?.as_bytes();
+        match hex.len() {
+            n if n < hex_len => Err(EnvelopeError::Truncated),
+            n if n > hex_len => Err(EnvelopeError::TrailingData),
+            _ => {
+                let payload: Protected<Vec<u8>> = decode_hex(hex)?;
+                T::Inner::from_payload_bytes(payload.inner())
+                    .map(T::init_from_inner)
+                    .ok_or(EnvelopeError::InvalidPayload)
+            }
+        }
+    }
+}
+
+struct TypeIdCheck<T>(core::marker::PhantomData<T>);
+
+impl<T: TypeIdentifier> TypeIdCheck<T> {
+    const VALID: &'static str = {
+        assert!(
+            is_valid_type_id(T::TYPE_ID),
+            "TYPE_ID must be 1 to 255 printable ASCII characters and must not contain ':'"
+        );
+        T::TYPE_ID
+    };
+}
+
+/// Returns the type ID of `T`, failing to compile if it is invalid.
+fn valid_type_id<T: TypeIdentifier>() -> &'static str {
+    TypeIdCheck::<T>::VALID
+}
+
+const fn is_valid_type_id(id: &str) -> bool {
+    let bytes = id.as_bytes();
+    if bytes.is_empty() || bytes.len() > u8::MAX as usize {
+        return false;
+    }
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_graphic() || bytes[i] == b':' {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+fn header_len(type_id: &str) -> usize {
+    MAGIC.len() + 1 + 1 + type_id.len() + 4
+}
+
+fn write_header(out: &mut Vec<u8>, type_id: &str, payload_len: u32) {
+    out.extend_from_slice(&MAGIC);
+    out.push(ENVELOPE_VERSION);
+    out.push(type_id.len() as u8);
+    out.extend_from_slice(type_id.as_bytes());
+    out.extend_from_slice(&payload_len.to_be_bytes());
+}
+
+/// Payloads larger than 4GiB are not supported.
+fn check_payload_len(len: usize) -> Result<u32, EnvelopeError> {
+    u32::try_from(len).map_err(|_| EnvelopeError::PayloadTooLarge(len))
+}
+
+fn split(bytes: &[u8], at: usize) -> Result<(&[u8], &[u8]), EnvelopeError> {
+    if bytes.len() < at {
+        Err(EnvelopeError::Truncated)
+    } else {
+        Ok(bytes.split_at(at))
+    }
+}
+
+fn check_version(version: u8) -> Result<(), EnvelopeError> {
+    if version == ENVELOPE_VERSION {
+        Ok(())
+    } else {
+        Err(EnvelopeError::UnsupportedVersion(version))
+    }
+}
+
+fn check_type_id(expected: &'static str, found: &[u8]) -> Result<(), EnvelopeError> {
+    if expected.as_bytes() == found {
+        Ok(())
+    } else {
+        Err(EnvelopeError::TypeMismatch {
+            expected,
+            found: String::from_utf8_lossy(found).into_owned(),
+        })
+    }
+}
+
+fn check_len<P: EnvelopePayload>(found: usize) -> Result<(), EnvelopeError> {
+    match P::expected_len() {
+        Some(expected) if expected != found => {
+            Err(EnvelopeError::LengthMismatch { expected, found })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Constant time conversion of a nibble to a lowercase hex character.
+#[inline]
+fn encode_nibble(nibble: u8) -> char {
+    let n = nibble as i16;
+    // Adds 39 (the gap between '9' and 'a') when n > 9 without branching
+    (n + 0x30 + (((0x39 - 0x30 - n) >> 8) & (0x61 - 0x3a))) as u8 as char
+}
+
+/// Constant time conversion of a lowercase hex character to a nibble.
+/// Returns a value greater than `0x0f` if the character is invalid.
+#[inline]
+fn decode_nibble(c: u8) -> u16 {
+    let c = c as i16;
+    let mut n: i16 = -1;
+    // 0-9
+    n += (((0x2f - c) & (c - 0x3a)) >> 8) & (c - 47);
+    // a-f
+    n += (((0x60 - c) & (c - 0x67)) >> 8) & (c - 86);
+    n as u16
+}
+
+fn decode_hex(hex: &[u8]) -> Result<Protected<Vec<u8>>, EnvelopeError> {
+    let mut invalid: u16 = 0;
+    let mut out = Protected::generate(|| Vec::with_capacity(hex.len() / 2));
+    out.update(|out| {
+        hex.chunks_exact(2).for_each(|pair| {
+            let mut byte = (decode_nibble(pair[0]) << 4) | decode_nibble(pair[1]);
+            invalid |= byte >> 8;
+            out.push(byte as u8);
+            byte.zeroize();
+        })
+    });
+
+    if invalid == 0 {
+        Ok(out)
+    } else {
+        out.zeroize();
+        Err(EnvelopeError::InvalidEncoding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Equatable, Exportable};
+    use std::string::ToString;
+
+    struct MacKey;
+    impl Scope for MacKey {}
+    impl TypeIdentifier for MacKey {
+        const TYPE_ID: &'static str = "test.mac-key";
+    }
+
+    struct PermutationKey;
+    impl Scope for PermutationKey {}
+    impl TypeIdentifier for PermutationKey {
+        const TYPE_ID: &'static str = "test.permutation-key";
+    }
+
+    type Mac = Usage<Exportable<Protected<[u8; 32]>>, MacKey>;
+    type Permutation = Usage<Exportable<Protected<[u8; 32]>>, PermutationKey>;
+    type Password = Usage<Equatable<Exportable<Protected<String>>>, MacKey>;
+
+    #[test]
+    fn test_binary_roundtrip() {
+        let bytes = Mac::new([42; 32]).to_envelope().unwrap();
+        let key = Mac::from_envelope(bytes.inner()).unwrap();
+        assert_eq!(key.risky_unwrap(), [42; 32]);
+    }
+
+    #[test]
+    fn test_binary_header() {
+        let bytes = Mac::new([1; 32]).to_envelope().unwrap().risky_unwrap();
+        assert_eq!(&bytes[0..4], b"VTMC");
+        assert_eq!(bytes[4], ENVELOPE_VERSION);
+        assert_eq!(bytes[5] as usize, "test.mac-key".len());
+        assert_eq!(&bytes[6..18], b"test.mac-key");
+        assert_eq!(&bytes[18..22], &32u32.to_be_bytes());
+        assert_eq!(&bytes[22..], &[1; 32]);
+    }
+
+    #[test]
+    fn test_text_roundtrip() {
+        let text = Mac::new([0xab; 32]).to_envelope_string().unwrap();
+        let key = Mac::from_envelope_str(text.inner()).unwrap();
+        assert_eq!(key.risky_unwrap(), [0xab; 32]);
+    }
+
+    #[test]
+    fn test_text_format() {
+        let text = Mac::new([0x0f; 32])
+            .to_envelope_string()
+            .unwrap()
+            .risky_unwrap();
+        assert_eq!(
+            text,
+            format!("vitaminc:1:test.mac-key:32:{}", "0f".repeat(32))
+        );
+    }
+
+    #[test]
+    fn test_string_roundtrip() {
+        let value = Password::new("correct horse battery staple".to_string());
+        let bytes = value.to_envelope().unwrap();
+        let text = value.to_envelope_string().unwrap();
+        let from_bytes = Password::from_envelope(bytes.inner()).unwrap();
+        let from_text = Password::from_envelope_str(text.inner()).unwrap();
+        assert_eq!(from_bytes.risky_unwrap(), value.inner().as_str());
+        assert_eq!(from_text.risky_unwrap(), "correct horse battery staple");
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        let bytes = Mac::new([0; 32]).to_envelope().unwrap();
+        assert_eq!(
+            Permutation::from_envelope(bytes.inner()).err(),
+            Some(EnvelopeError::TypeMismatch {
+                expected: "test.permutation-key",
+                found: "test.mac-key".to_string()
+            })
+        );
+
+        let text = Mac::new([0; 32]).to_envelope_string().unwrap();
+        assert!(matches!(
+            Permutation::from_envelope_str(text.inner()),
+            Err(EnvelopeError::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_wrong_length() {
+        type Short = Usage<Exportable<Protected<[u8; 16]>>, MacKey>;
+        let bytes = Mac::new([0; 32]).to_envelope().unwrap();
+        assert_eq!(
+            Short::from_envelope(bytes.inner()).err(),
+            Some(EnvelopeError::LengthMismatch {
+                expected: 16,
+                found: 32
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        let mut bytes = Mac::new([0; 32]).to_envelope().unwrap().risky_unwrap();
+        bytes[4] = 2;
+        assert_eq!(
+            Mac::from_envelope(&bytes).err(),
+            Some(EnvelopeError::UnsupportedVersion(2))
+        );
+        bytes[0] = b'X';
+        assert_eq!(
+            Mac::from_envelope(&bytes).err(),
+            Some(EnvelopeError::InvalidMagic)
+        );
+        assert_eq!(
+            Mac::from_envelope(&[]).err(),
+            Some(EnvelopeError::Truncated)
+        );
+    }
+
+    #[test]
+    fn test_truncated_and_trailing() {
+        let bytes = Mac::new([0; 32]).to_envelope().unwrap().risky_unwrap();
+        assert_eq!(
+            Mac::from_envelope(&bytes[..bytes.len() - 1]).err(),
+            Some(EnvelopeError::Truncated)
+        );
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert_eq!(
+            Mac::from_envelope(&extended).err(),
+            Some(EnvelopeError::TrailingData)
+        );
+    }
+
+    #[test]
+    fn test_invalid_hex() {
+        let text = format!("vitaminc:1:test.mac-key:32:{}", "zz".repeat(32));
+        assert_eq!(
+            Mac::from_envelope_str(&text).err(),
+            Some(EnvelopeError::InvalidEncoding)
+        );
+        // Upper case is not produced by the encoder and is rejected
+        let text = format!("vitaminc:1:test.mac-key:32:{}", "AB".repeat(32));
+        assert_eq!(
+            Mac::from_envelope_str(&text).err(),
+            Some(EnvelopeError::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn test_text_payload_len_is_bounded() {
+        type Bytes = Usage<Exportable<Protected<Vec<u8>>>, MacKey>;
+        for len in [u64::MAX.to_string(), (u32::MAX as u64 + 1).to_string()] {
+            let text = format!("vitaminc:1:test.mac-key:{len}:00");
+            assert_eq!(
+                Bytes::from_envelope_str(&text).err(),
+                Some(EnvelopeError::InvalidEncoding)
+            );
+        }
+        let text = format!("vitaminc:1:test.mac-key:{}:00", u32::MAX);
+        assert_eq!(
+            Bytes::from_envelope_str(&text).err(),
+            Some(EnvelopeError::Truncated)
+        );
+    }
+
+    #[test]
+    fn test_payload_too_large() {
+        assert_eq!(check_payload_len(u32::MAX as usize), Ok(u32::MAX));
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(
+            check_payload_len(u32::MAX as usize + 1),
+            Err(EnvelopeError::PayloadTooLarge(u32::MAX as usize + 1))
+        );
+    }
+
+    #[test]
+    fn test_hex_nibbles() {
+        for n in 0..16u8 {
+            let c = encode_nibble(n);
+            assert_eq!(c, char::from_digit(n as u32, 16).unwrap());
+            assert_eq!(decode_nibble(c as u8), n as u16);
+        }
+        for c in (0..=255u8).filter(|c| !c.is_ascii_digit() && !(b'a'..=b'f').contains(c)) {
+            assert!(decode_nibble(c) > 0x0f, "{c} should be invalid");
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{private::ControlledPrivate, AccessError, Controlled};
+use core::{
+    cell::{Cell, RefCell},
+    fmt::Debug,
+};
+use zeroize::Zeroize;
+
+/// A _controlled_ wrapper for secrets that must only be used once, such as one-time tokens and nonces.
+///
+/// The first access to the inner value (via any [Controlled] method, [crate::Equatable] comparison,
+/// serialization or [Ephemeral::try_expose]) consumes the secret and zeroizes it as soon as that access returns.
+/// Any further access is an error: `try_expose` returns [AccessError::AlreadyUsed], [crate::Equatable]
+/// comparisons return `false` and other methods panic.
+///
+/// Like [crate::Masked], the inner value is only ever lent to a closure (e.g. [Controlled::expose])
+/// so that it can be zeroized afterwards.
+///
+/// Ephemeral values are not `Sync` so the used flag can't be raced across threads.
+///
+/// # Example
+///
+/// ```
+/// use vitaminc_protected::{AccessError, Controlled, Ephemeral, Protected};
+///
+/// let nonce: Ephemeral<Protected<[u8; 12]>> = Ephemeral::new([7; 12]);
+/// assert_eq!(nonce.try_expose(|n| n[0]), Ok(7));
+/// assert_eq!(nonce.try_expose(|n| n[0]), Err(AccessError::AlreadyUsed));
+/// ```
+///
+/// # Composing with `Equatable`
+///
+/// ```
+/// use vitaminc_protected::{Controlled, Ephemeral, Equatable, Protected};
+///
+/// type Token = Equatable<Ephemeral<Protected<[u8; 16]>>>;
+///
+/// let issued = Token::new([1; 16]);
+/// let presented = Token::new([1; 16]);
+/// assert_eq!(issued, presented);
+///
+/// // The token can't be replayed
+/// assert_ne!(issued, Token::new([1; 16]));
+/// ```
+pub struct Ephemeral<T> {
+    inner: RefCell<T>,
+    used: Cell<bool>,
+}
+
+impl<T: Zeroize> Ephemeral<T> {
+    /// Create a new unused [Ephemeral] from an inner value.
+    pub fn new(x: <Ephemeral<T> as ControlledPrivate>::Inner) -> Self
+    where
+        Self: ControlledPrivate,
+    {
+        Self::init_from_inner(x)
+    }
+
+    /// Returns `true` if the secret has been used.
+    pub fn is_used(&self) -> bool {
+        self.used.get()
+    }
+
+    /// Mark the secret as used, returning a guard that zeroizes it when the access ends.
+    fn use_once(&self) -> Result<Wipe<'_, T>, AccessError> {
+        if self.used.replace(true) {
+            self.wipe();
+            Err(AccessError::AlreadyUsed)
+        } else {
+            Ok(Wipe(&self.inner))
+        }
+    }
+
+    /// Zeroize the inner value unless it is borrowed by an access that is still in progress
+    /// (which zeroizes it when it returns).
+    fn wipe(&self) {
+        if let Ok(mut inner) = self.inner.try_borrow_mut() {
+            inner.zeroize();
+        }
+    }
+}
+
+/// Zeroizes the inner value of an [Ephemeral] on drop (including when the closure it was lent to panics).
+struct Wipe<'a, T: Zeroize>(&'a RefCell<T>);
+
+impl<T: Zeroize> Drop for Wipe<'_, T> {
+    fn drop(&mut self) {
+        if let Ok(mut inner) = self.0.try_borrow_mut() {
+            inner.zeroize();
+        }
+    }
+}
+
+impl<T> Ephemeral<T>
+where
+    T: ControlledPrivate + Zeroize,
+{
+    /// Use the secret by passing a reference to a closure, or return an error if it has already been used.
+    #[cfg_attr(feature = "audit", track_caller)]
+    pub fn try_expose<R, F>(&self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&T::Inner) -> R,
+    {
+        let result = self.try_with_inner(f);
+        #[cfg(feature = "audit")]
+        if result.is_ok() {
+            crate::audit::record::<Self>(crate::audit::ExposureKind::Expose);
+        }
+        result
+    }
+}
+
+impl<T> ControlledPrivate for Ephemeral<T>
+where
+    T: ControlledPrivate + Zeroize,
+{
+    type Inner = T::Inner;
+
+    fn init_from_inner(x: Self::Inner) -> Self {
+        Self {
+            inner: RefCell::new(T::init_from_inner(x)),
+            used: Cell::new(false),
+        }
+    }
+
+    fn inner(&self) -> &Self::Inner {
+        let _wipe = self.use_once();
+        panic!(
+            "Ephemeral values can only be accessed inside a closure (e.g. `Controlled::expose`)"
+        );
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        let _wipe = self.use_once();
+        panic!(
+            "Ephemeral values can only be accessed inside a closure (e.g. `Controlled::expose_mut`)"
+        );
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        // A used value has already been zeroized
+        if self.used.replace(true) {
+            panic!("{}", AccessError::AlreadyUsed);
+        }
+        self.inner.into_inner().into_inner()
+    }
+
+    fn with_inner<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&Self::Inner) -> R,
+    {
+        self.try_with_inner(f).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn try_with_inner<R, F>(&self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&Self::Inner) -> R,
+    {
+        let _wipe = self.use_once()?;
+        let inner = self.inner.borrow();
+        inner.try_with_inner(f)
+    }
+
+    fn with_inner_mut<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Self::Inner) -> R,
+    {
+        self.try_with_inner_mut(f).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn try_with_inner_mut<R, F>(&mut self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&mut Self::Inner) -> R,
+    {
+        let _wipe = self.use_once()?;
+        let mut inner = self.inner.borrow_mut();
+        inner.try_with_inner_mut(f)
+    }
+
+    fn scope_name() -> Option<&'static str> {
+        T::scope_name()
+    }
+}
+
+impl<T> Controlled for Ephemeral<T> where T: Controlled + Zeroize {}
+
+impl<T: Zeroize> Zeroize for Ephemeral<T> {
+    fn zeroize(&mut self) {
+        self.inner.get_mut().zeroize();
+    }
+}
+
+impl<T: Debug + Zeroize> Debug for Ephemeral<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Ephemeral")
+            .field(&*self.inner.borrow())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Equatable, Protected};
+    use std::format;
+
+    #[test]
+    fn test_single_use() {
+        let x: Ephemeral<Protected<[u8; 4]>> = Ephemeral::new([1; 4]);
+        assert!(!x.is_used());
+        assert_eq!(x.try_expose(|x| x.len()), Ok(4));
+        assert!(x.is_used());
+        assert_eq!(x.try_expose(|x| x.len()), Err(AccessError::AlreadyUsed));
+    }
+
+    #[test]
+    #[should_panic(expected = "Secret has already been used")]
+    fn test_expose_after_use_panics() {
+        let x: Ephemeral<Protected<u8>> = Ephemeral::new(1);
+        x.expose(|_| ());
+        x.expose(|_| ());
+    }
+
+    #[test]
+    fn test_risky_unwrap_unused() {
+        let x: Ephemeral<Protected<u8>> = Ephemeral::new(42);
+        assert_eq!(x.risky_unwrap(), 42);
+    }
+
+    #[test]
+    fn test_zeroized_after_use() {
+        let x: Ephemeral<Protected<[u8; 4]>> = Ephemeral::new([1; 4]);
+        assert_eq!(x.expose(|x| x[0]), 1);
+        assert_eq!(x.inner.borrow().0, [0; 4]);
+
+        let mut y: Ephemeral<Protected<[u8; 4]>> = Ephemeral::new([1; 4]);
+        y.update(|y| y[0] = 2);
+        assert_eq!(y.inner.borrow().0, [0; 4]);
+    }
+
+    #[test]
+    fn test_zeroized_when_closure_panics() {
+        let x: Ephemeral<Protected<[u8; 4]>> = Ephemeral::new([1; 4]);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            x.expose(|_| panic!("oops"));
+        }));
+        assert!(result.is_err());
+        assert_eq!(x.inner.borrow().0, [0; 4]);
+    }
+
+    #[test]
+    fn test_nested_access_is_an_error() {
+        let x: Ephemeral<Protected<[u8; 4]>> = Ephemeral::new([1; 4]);
+        let nested = x.expose(|_| x.try_expose(|x| x[0]));
+        assert_eq!(nested, Err(AccessError::AlreadyUsed));
+        assert_eq!(x.inner.borrow().0, [0; 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Secret has already been used")]
+    fn test_mutable_access_after_use_panics() {
+        let mut x: Ephemeral<Protected<[u8; 4]>> = Ephemeral::new([1; 4]);
+        x.expose(|_| ());
+        x.expose_mut(|_| ());
+    }
+
+    #[test]
+    #[should_panic(expected = "Ephemeral values can only be accessed inside a closure")]
+    fn test_inner_panics() {
+        let x: Ephemeral<Protected<u8>> = Ephemeral::new(1);
+        x.inner();
+    }
+
+    #[test]
+    fn test_equatable_single_use() {
+        let x: Equatable<Ephemeral<Protected<[u8; 4]>>> = Equatable::new([1; 4]);
+        let y: Equatable<Ephemeral<Protected<[u8; 4]>>> = Equatable::new([1; 4]);
+        assert_eq!(x, y);
+
+        // Comparing again returns false rather than panicking
+        assert!(!x.constant_time_eq(&Equatable::new([1; 4])));
+        assert_ne!(Equatable::<Protected<[u8; 4]>>::new([1; 4]), y);
+
+        let z: Equatable<Ephemeral<Protected<[u8; 4]>>> = Equatable::new([1; 4]);
+        assert_ne!(z, z);
+    }
+
+    #[test]
+    fn test_opaque_debug() {
+        let x: Ephemeral<Protected<u8>> = Ephemeral::new(1);
+        assert_eq!(format!("{x:?}"), "Ephemeral(Protected<u8> { ... })");
+        assert!(!x.is_used());
+    }
+}
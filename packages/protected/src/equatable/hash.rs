@@ -0,0 +1,110 @@
+use super::{ConstantTimeEq, Equatable};
+use crate::private::ControlledPrivate;
+use std::{
+    hash::{BuildHasher, Hash, Hasher, RandomState},
+    sync::OnceLock,
+};
+
+/// Process-random SipHash key (from the standard library's `RandomState`), generated on first use.
+static HASH_KEY: OnceLock<RandomState> = OnceLock::new();
+
+/// [Equatable] values can be used as `HashMap` and `HashSet` keys.
+///
+/// The inner bytes are never passed to the map's hasher (which may be predictable).
+/// Instead, they are hashed with SipHash under a secret, process-random key
+/// and only the resulting 64-bit digest is fed to the hasher.
+/// Digests are only comparable within the same process.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use vitaminc_protected::{Equatable, Protected};
+///
+/// type SessionToken = Equatable<Protected<[u8; 32]>>;
+///
+/// let mut sessions: HashMap<SessionToken, &str> = HashMap::new();
+/// sessions.insert(SessionToken::new([1; 32]), "alice");
+/// assert_eq!(sessions.get(&SessionToken::new([1; 32])), Some(&"alice"));
+/// ```
+impl<T> Hash for Equatable<T>
+where
+    T: ControlledPrivate,
+    T::Inner: AsRef<[u8]>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.with_inner(|x| keyed_hash(x.as_ref())));
+    }
+}
+
+/// Needed for `HashMap` keys. Equality is checked in constant time.
+impl<T> Eq for Equatable<T>
+where
+    T: ControlledPrivate,
+    T::Inner: ConstantTimeEq,
+{
+}
+
+fn keyed_hash(bytes: &[u8]) -> u64 {
+    HASH_KEY.get_or_init(RandomState::new).hash_one(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Exportable, Protected};
+    use std::collections::{HashMap, HashSet};
+
+    type Token = Equatable<Protected<[u8; 32]>>;
+
+    /// A predictable hasher that records everything written to it.
+    #[derive(Default)]
+    struct Recorder(Vec<u8>);
+
+    impl Hasher for Recorder {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.extend_from_slice(bytes);
+        }
+    }
+
+    #[test]
+    fn test_hash_map() {
+        let mut map: HashMap<Token, u32> = HashMap::new();
+        map.insert(Token::new([1; 32]), 1);
+        map.insert(Token::new([2; 32]), 2);
+        assert_eq!(map.get(&Token::new([1; 32])), Some(&1));
+        assert_eq!(map.get(&Token::new([2; 32])), Some(&2));
+        assert_eq!(map.get(&Token::new([3; 32])), None);
+    }
+
+    #[test]
+    fn test_hash_set_string() {
+        let mut set: HashSet<Equatable<Exportable<Protected<String>>>> = HashSet::new();
+        set.insert(Equatable::new("token".to_string()));
+        assert!(set.contains(&Equatable::new("token".to_string())));
+        assert!(!set.contains(&Equatable::new("other".to_string())));
+    }
+
+    #[test]
+    fn test_hasher_never_sees_inner_bytes() {
+        let mut recorder = Recorder::default();
+        Token::new([0xab; 32]).hash(&mut recorder);
+        assert_eq!(recorder.0.len(), 8);
+        assert_ne!(recorder.0, [0xab; 8]);
+    }
+
+    #[test]
+    fn test_equal_values_hash_equal() {
+        let hash = |x: &Token| {
+            let mut recorder = Recorder::default();
+            x.hash(&mut recorder);
+            recorder.0
+        };
+        assert_eq!(hash(&Token::new([5; 32])), hash(&Token::new([5; 32])));
+        assert_ne!(hash(&Token::new([5; 32])), hash(&Token::new([6; 32])));
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{exportable::SafeSerialize, private::ControlledPrivate, Controlled, Protected};
+#[cfg(feature = "std")]
+mod hash;
+
+use crate::{
+    exportable::SafeSerialize, private::ControlledPrivate, AccessError, Controlled, Protected,
+};
 use core::num::NonZeroU16;
 use serde::{Serialize, Serializer};
 use subtle::ConstantTimeEq as SubtleCtEq;
@@ -117,7 +122,10 @@ where
     T::Inner: ConstantTimeEq,
 {
     pub fn constant_time_eq(&self, other: &Self) -> bool {
-        self.inner().constant_time_eq(other.inner())
+        matches!(
+            self.try_with_inner(|a| other.try_with_inner(|b| a.constant_time_eq(b))),
+            Ok(Ok(true))
+        )
     }
 }
 
@@ -136,17 +144,46 @@ impl<T: ControlledPrivate> ControlledPrivate for Equatable<T> {
     fn inner_mut(&mut self) -> &mut Self::Inner {
         self.0.inner_mut()
     }
-}
 
-impl<T> Controlled for Equatable<T>
-where
-    T: Controlled,
-{
-    fn risky_unwrap(self) -> Self::Inner {
-        self.0.risky_unwrap()
+    fn into_inner(self) -> Self::Inner {
+        self.0.into_inner()
+    }
+
+    fn with_inner<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&Self::Inner) -> R,
+    {
+        self.0.with_inner(f)
+    }
+
+    fn try_with_inner<R, F>(&self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&Self::Inner) -> R,
+    {
+        self.0.try_with_inner(f)
+    }
+
+    fn with_inner_mut<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Self::Inner) -> R,
+    {
+        self.0.with_inner_mut(f)
+    }
+
+    fn try_with_inner_mut<R, F>(&mut self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&mut Self::Inner) -> R,
+    {
+        self.0.try_with_inner_mut(f)
+    }
+
+    fn scope_name() -> Option<&'static str> {
+        T::scope_name()
     }
 }
 
+impl<T> Controlled for Equatable<T> where T: Controlled {}
+
 // TODO: Further constrain this
 impl<T> From<T> for Equatable<Protected<T>>
 where
@@ -165,7 +202,10 @@ where
     <T as ControlledPrivate>::Inner: ConstantTimeEq<O::Inner>,
 {
     fn eq(&self, other: &O) -> bool {
-        self.inner().constant_time_eq(other.inner())
+        matches!(
+            self.try_with_inner(|a| other.try_with_inner(|b| a.constant_time_eq(b))),
+            Ok(Ok(true))
+        )
     }
 }
 
@@ -176,7 +216,10 @@ where
     <T as ControlledPrivate>::Inner: ConstantTimeEq<O::Inner>,
 {
     fn constant_time_eq(&self, other: &O) -> bool {
-        self.inner().constant_time_eq(other.inner())
+        matches!(
+            self.try_with_inner(|a| other.try_with_inner(|b| a.constant_time_eq(b))),
+            Ok(Ok(true))
+        )
     }
 }
 
@@ -259,7 +302,8 @@ impl ConstantTimeEq for str {
     }
 }
 
-impl ConstantTimeEq for String {
+#[cfg(feature = "alloc")]
+impl ConstantTimeEq for alloc::string::String {
     /// Check whether two strings are equal.
     ///
     /// This function short-circuits if the lengths of the input strings
@@ -279,12 +323,14 @@ where
     where
         S: Serializer,
     {
-        self.inner().safe_serialize(serializer)
+        #[cfg(feature = "audit")]
+        crate::audit::record::<Self>(crate::audit::ExposureKind::Export);
+        self.with_inner(|x| x.safe_serialize(serializer))
     }
 }
 
 mod private {
-    use std::num::NonZeroU16;
+    use core::num::NonZeroU16;
 
     use super::Equatable;
 
@@ -307,13 +353,15 @@ mod private {
     impl SupportsConstantTimeEq for isize {}
     impl SupportsConstantTimeEq for NonZeroU16 {}
     impl SupportsConstantTimeEq for [u8] {}
-    impl SupportsConstantTimeEq for String {}
+    #[cfg(feature = "alloc")]
+    impl SupportsConstantTimeEq for alloc::string::String {}
     impl SupportsConstantTimeEq for str {}
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{Equatable, Protected};
+    use std::format;
 
     #[test]
     fn test_opaque_debug() {
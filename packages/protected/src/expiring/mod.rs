@@ -0,0 +1,355 @@
+use crate::{private::ControlledPrivate, AccessError, Controlled};
+use std::{
+    fmt::Debug,
+    sync::{RwLock, RwLockReadGuard, TryLockError},
+    time::{Duration, Instant},
+};
+use zeroize::Zeroize;
+
+/// A _controlled_ wrapper for short-lived secrets such as session keys.
+///
+/// The value expires `TTL_SECS` seconds after it is created (or at an explicit deadline set with
+/// [Expiring::with_deadline]). The deadline is checked on every access to the inner value:
+/// [Expiring::try_expose] returns [AccessError::Expired], [crate::Equatable] comparisons return `false`
+/// and other methods panic.
+///
+/// Any access after the deadline zeroizes the inner value, as does the end of an access that was still in progress
+/// when the deadline passed. A long-lived owner should also call [Expiring::zeroize_if_expired] periodically
+/// so that a secret that is never accessed again doesn't stay in memory.
+///
+/// Like [crate::Masked], the inner value is only ever lent to a closure (e.g. [Controlled::expose]).
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use vitaminc_protected::{AccessError, Controlled, Expiring, Protected};
+///
+/// // Expires 5 minutes after creation
+/// let session: Expiring<Protected<[u8; 32]>, 300> = Expiring::new([1; 32]);
+/// assert_eq!(session.try_expose(|key| key.len()), Ok(32));
+///
+/// let mut expired: Expiring<Protected<[u8; 32]>, 300> = Expiring::with_deadline([1; 32], Instant::now());
+/// assert_eq!(expired.try_expose(|key| key.len()), Err(AccessError::Expired));
+/// ```
+///
+/// A TTL of zero would expire immediately so it is rejected at compile time:
+///
+/// ```compile_fail
+/// use vitaminc_protected::{Controlled, Expiring, Protected};
+///
+/// let session: Expiring<Protected<[u8; 32]>, 0> = Expiring::new([1; 32]);
+/// ```
+///
+/// # Composing with `Equatable`
+///
+/// ```
+/// use vitaminc_protected::{Controlled, Equatable, Expiring, Protected};
+///
+/// let x: Equatable<Expiring<Protected<u64>, 60>> = Equatable::new(42);
+/// let y: Equatable<Expiring<Protected<u64>, 60>> = Equatable::new(42);
+/// assert_eq!(x, y);
+/// ```
+pub struct Expiring<T, const TTL_SECS: u64> {
+    inner: RwLock<T>,
+    /// `None` if the deadline is too far in the future to represent (i.e. the value never expires).
+    deadline: Option<Instant>,
+}
+
+impl<T, const TTL_SECS: u64> Expiring<T, TTL_SECS> {
+    const NONZERO_TTL: () = assert!(TTL_SECS > 0, "Expiring TTL_SECS must be greater than 0");
+
+    /// Create a new [Expiring] that expires `TTL_SECS` from now.
+    pub fn new(x: <Expiring<T, TTL_SECS> as ControlledPrivate>::Inner) -> Self
+    where
+        Self: ControlledPrivate,
+    {
+        Self::init_from_inner(x)
+    }
+
+    /// The instant at which the value expires,
+    /// or `None` if `TTL_SECS` is too large for the deadline to be represented by an [Instant].
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<T, const TTL_SECS: u64> Expiring<T, TTL_SECS>
+where
+    T: ControlledPrivate + Zeroize,
+{
+    /// Create a new [Expiring] that expires at `deadline` instead of after `TTL_SECS`.
+    pub fn with_deadline(x: T::Inner, deadline: Instant) -> Self {
+        Self {
+            inner: RwLock::new(T::init_from_inner(x)),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// Pass a reference to the inner value to a closure, or return an error if the value has expired.
+    #[cfg_attr(feature = "audit", track_caller)]
+    pub fn try_expose<R, F>(&self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&T::Inner) -> R,
+    {
+        let result = self.try_with_inner(f);
+        #[cfg(feature = "audit")]
+        if result.is_ok() {
+            crate::audit::record::<Self>(crate::audit::ExposureKind::Expose);
+        }
+        result
+    }
+
+    /// Zeroize the inner value if it has expired, returning `true` if it had.
+    pub fn zeroize_if_expired(&mut self) -> bool {
+        let expired = self.is_expired();
+        if expired {
+            self.zeroize();
+        }
+        expired
+    }
+
+    /// Check the deadline, zeroizing the inner value if it has passed.
+    fn check(&self) -> Result<(), AccessError> {
+        if self.is_expired() {
+            self.wipe();
+            Err(AccessError::Expired)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Zeroize the inner value unless an access is in progress (the last access to end zeroizes it instead).
+    fn wipe(&self) {
+        match self.inner.try_write() {
+            Ok(mut inner) => inner.zeroize(),
+            Err(TryLockError::Poisoned(e)) => e.into_inner().zeroize(),
+            Err(TryLockError::WouldBlock) => {}
+        }
+    }
+}
+
+impl<T, const TTL_SECS: u64> ControlledPrivate for Expiring<T, TTL_SECS>
+where
+    T: ControlledPrivate + Zeroize,
+{
+    type Inner = T::Inner;
+
+    fn init_from_inner(x: Self::Inner) -> Self {
+        let () = Self::NONZERO_TTL;
+        Self {
+            inner: RwLock::new(T::init_from_inner(x)),
+            deadline: Instant::now().checked_add(Duration::from_secs(TTL_SECS)),
+        }
+    }
+
+    fn inner(&self) -> &Self::Inner {
+        let _ = self.check();
+        panic!("Expiring values can only be accessed inside a closure (e.g. `Controlled::expose`)");
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        if self.zeroize_if_expired() {
+            panic!("{}", AccessError::Expired);
+        }
+        self.inner
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .inner_mut()
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        if let Err(e) = self.check() {
+            panic!("{e}");
+        }
+        self.inner
+            .into_inner()
+            .unwrap_or_else(|e| e.into_inner())
+            .into_inner()
+    }
+
+    fn with_inner<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&Self::Inner) -> R,
+    {
+        self.try_with_inner(f).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn try_with_inner<R, F>(&self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&Self::Inner) -> R,
+    {
+        self.check()?;
+        let result = self.read().try_with_inner(f);
+        // Zeroize if the deadline passed while the closure was running
+        if self.is_expired() {
+            self.wipe();
+        }
+        result
+    }
+
+    fn with_inner_mut<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Self::Inner) -> R,
+    {
+        self.try_with_inner_mut(f).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn try_with_inner_mut<R, F>(&mut self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&mut Self::Inner) -> R,
+    {
+        if self.zeroize_if_expired() {
+            return Err(AccessError::Expired);
+        }
+        let result = self
+            .inner
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .try_with_inner_mut(f);
+        self.zeroize_if_expired();
+        result
+    }
+
+    fn scope_name() -> Option<&'static str> {
+        T::scope_name()
+    }
+}
+
+impl<T, const TTL_SECS: u64> Controlled for Expiring<T, TTL_SECS> where T: Controlled + Zeroize {}
+
+impl<T: Zeroize, const TTL_SECS: u64> Zeroize for Expiring<T, TTL_SECS> {
+    fn zeroize(&mut self) {
+        self.inner
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .zeroize();
+    }
+}
+
+impl<T: Debug, const TTL_SECS: u64> Debug for Expiring<T, TTL_SECS> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Expiring").field(&*self.read()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Equatable, Protected};
+
+    fn expired() -> Expiring<Protected<[u8; 4]>, 60> {
+        Expiring::with_deadline([1; 4], Instant::now())
+    }
+
+    #[test]
+    fn test_not_expired() {
+        let x: Expiring<Protected<[u8; 4]>, 60> = Expiring::new([1; 4]);
+        assert!(!x.is_expired());
+        assert!(x.deadline().unwrap() > Instant::now());
+        assert_eq!(x.try_expose(|x| x[0]), Ok(1));
+        assert_eq!(x.try_expose(|x| x[0]), Ok(1));
+        assert_eq!(x.risky_unwrap(), [1; 4]);
+    }
+
+    #[test]
+    fn test_expired() {
+        let x = expired();
+        assert!(x.is_expired());
+        assert_eq!(x.try_expose(|x| x[0]), Err(AccessError::Expired));
+    }
+
+    #[test]
+    fn test_deadline_saturates() {
+        let x: Expiring<Protected<u8>, { u64::MAX }> = Expiring::new(1);
+        assert_eq!(x.deadline(), None);
+        assert!(!x.is_expired());
+        assert_eq!(x.try_expose(|x| *x), Ok(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Secret has expired")]
+    fn test_expose_expired_panics() {
+        expired().expose(|_| ());
+    }
+
+    #[test]
+    #[should_panic(expected = "Expiring values can only be accessed inside a closure")]
+    fn test_inner_panics() {
+        let x: Expiring<Protected<u8>, 60> = Expiring::new(1);
+        x.inner();
+    }
+
+    #[test]
+    fn test_zeroize_if_expired() {
+        let mut x = expired();
+        assert!(x.zeroize_if_expired());
+        assert_eq!(x.read().0, [0; 4]);
+
+        let mut y: Expiring<Protected<[u8; 4]>, 60> = Expiring::new([1; 4]);
+        assert!(!y.zeroize_if_expired());
+        assert_eq!(y.risky_unwrap(), [1; 4]);
+    }
+
+    #[test]
+    fn test_every_access_zeroizes() {
+        let x = expired();
+        assert!(x.try_expose(|_| ()).is_err());
+        assert_eq!(x.read().0, [0; 4]);
+
+        let x = expired();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| x.expose(|_| ())));
+        assert!(result.is_err());
+        assert_eq!(x.read().0, [0; 4]);
+
+        let mut x = expired();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            x.update(|x| x[0] = 2);
+        }));
+        assert!(result.is_err());
+        assert_eq!(x.read().0, [0; 4]);
+
+        let x: Equatable<Expiring<Protected<[u8; 4]>, 60>> =
+            Equatable(Expiring::with_deadline([1; 4], Instant::now()));
+        assert!(!x.constant_time_eq(&Equatable::new([1; 4])));
+        assert_eq!(x.0.read().0, [0; 4]);
+    }
+
+    #[test]
+    fn test_expiry_during_access_zeroizes() {
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let x: Expiring<Protected<[u8; 4]>, 60> = Expiring::with_deadline([1; 4], deadline);
+        let first = x.expose(|x| {
+            std::thread::sleep(Duration::from_millis(100));
+            x[0]
+        });
+        assert_eq!(first, 1);
+        assert_eq!(x.read().0, [0; 4]);
+    }
+
+    #[test]
+    fn test_equatable() {
+        let x: Equatable<Expiring<Protected<[u8; 4]>, 60>> = Equatable::new([1; 4]);
+        let y: Equatable<Expiring<Protected<[u8; 4]>, 60>> = Equatable::new([2; 4]);
+        assert_ne!(x, y);
+
+        let expired: Equatable<Expiring<Protected<[u8; 4]>, 60>> =
+            Equatable(Expiring::with_deadline([1; 4], Instant::now()));
+        assert_ne!(expired, Equatable::<Protected<[u8; 4]>>::new([1; 4]));
+    }
+
+    #[test]
+    fn test_opaque_debug() {
+        let x: Expiring<Protected<u8>, 60> = Expiring::new(1);
+        assert_eq!(format!("{x:?}"), "Expiring(Protected<u8> { ... })");
+    }
+}
@@ -1,6 +1,6 @@
 mod safe_deserialize;
 mod safe_serialize;
-use crate::{equatable::ConstantTimeEq, private::ControlledPrivate, Controlled};
+use crate::{equatable::ConstantTimeEq, private::ControlledPrivate, AccessError, Controlled};
 use serde::{
     de::{Deserialize, Deserializer},
     ser::{Serialize, Serializer},
@@ -84,7 +84,7 @@ where
     <T as ControlledPrivate>::Inner: ConstantTimeEq<O::Inner>,
 {
     fn eq(&self, other: &O) -> bool {
-        self.inner().constant_time_eq(other.inner())
+        self.with_inner(|a| other.with_inner(|b| a.constant_time_eq(b)))
     }
 }
 
@@ -102,17 +102,46 @@ impl<T: ControlledPrivate> ControlledPrivate for Exportable<T> {
     fn inner_mut(&mut self) -> &mut Self::Inner {
         self.0.inner_mut()
     }
-}
 
-impl<T> Controlled for Exportable<T>
-where
-    T: Controlled,
-{
-    fn risky_unwrap(self) -> Self::Inner {
-        self.0.risky_unwrap()
+    fn into_inner(self) -> Self::Inner {
+        self.0.into_inner()
+    }
+
+    fn with_inner<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&Self::Inner) -> R,
+    {
+        self.0.with_inner(f)
+    }
+
+    fn try_with_inner<R, F>(&self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&Self::Inner) -> R,
+    {
+        self.0.try_with_inner(f)
+    }
+
+    fn with_inner_mut<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Self::Inner) -> R,
+    {
+        self.0.with_inner_mut(f)
+    }
+
+    fn try_with_inner_mut<R, F>(&mut self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&mut Self::Inner) -> R,
+    {
+        self.0.try_with_inner_mut(f)
+    }
+
+    fn scope_name() -> Option<&'static str> {
+        T::scope_name()
     }
 }
 
+impl<T> Controlled for Exportable<T> where T: Controlled {}
+
 impl<T> Serialize for Exportable<T>
 where
     T: ControlledPrivate,
@@ -122,7 +151,9 @@ where
     where
         S: Serializer,
     {
-        self.inner().safe_serialize(serializer)
+        #[cfg(feature = "audit")]
+        crate::audit::record::<Self>(crate::audit::ExposureKind::Export);
+        self.with_inner(|x| x.safe_serialize(serializer))
     }
 }
 
@@ -141,7 +172,11 @@ where
 
 #[cfg(test)]
 mod tests {
-    use std::fmt::Debug;
+    use std::{
+        fmt::Debug,
+        format,
+        string::{String, ToString},
+    };
 
     use super::*;
     use crate::{Equatable, Protected};
@@ -47,6 +47,6 @@ macro_rules! impl_safe_deserialize {
     };
 }
 
-impl_safe_deserialize!(
-    i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, f32, f64, bool, char, String
-);
+impl_safe_deserialize!(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, f32, f64, bool, char);
+#[cfg(feature = "alloc")]
+impl_safe_deserialize!(alloc::string::String);
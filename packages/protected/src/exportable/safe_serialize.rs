@@ -1,4 +1,4 @@
-use std::num::{
+use core::num::{
     NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
     NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
 };
@@ -7,6 +7,7 @@ use serde::ser::SerializeTuple;
 use serde::{Serialize, Serializer};
 
 use crate::private::ControlledPrivate;
+use zeroize::Zeroize;
 
 // TODO: Create a serialize method on exportable which maps into the serialized form
 // Exportable should also implement a "safe" version of Hex (serdect)
@@ -16,6 +17,20 @@ pub trait SafeSerialize {
     fn safe_serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer;
+
+    /// The length in bytes of the value, as reported in [crate::Redaction::Length] output.
+    fn redacted_len(&self) -> usize {
+        core::mem::size_of_val(self)
+    }
+
+    /// Call `f` with the bytes fingerprinted in [crate::Redaction::Fingerprint] output,
+    /// or `None` if the value has no byte representation.
+    fn with_redacted_bytes<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(Option<&[u8]>) -> R,
+    {
+        f(None)
+    }
 }
 
 /// Blanket implementation for all controlled types who's inner type implements `SafeSerialize`.
@@ -28,7 +43,18 @@ where
     where
         S: Serializer,
     {
-        self.inner().safe_serialize(serializer)
+        self.with_inner(|x| x.safe_serialize(serializer))
+    }
+
+    fn redacted_len(&self) -> usize {
+        self.with_inner(|x| x.redacted_len())
+    }
+
+    fn with_redacted_bytes<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(Option<&[u8]>) -> R,
+    {
+        self.with_inner(|x| x.with_redacted_bytes(f))
     }
 }
 
@@ -47,24 +73,50 @@ macro_rules! impl_safe_serialize {
     };
 }
 
-impl_safe_serialize!(u8, serialize_u8);
-impl_safe_serialize!(u16, serialize_u16);
-impl_safe_serialize!(u32, serialize_u32);
-impl_safe_serialize!(u64, serialize_u64);
-impl_safe_serialize!(u128, serialize_u128);
-impl_safe_serialize!(usize, serialize_u64 as u64);
-impl_safe_serialize!(i8, serialize_i8);
-impl_safe_serialize!(i16, serialize_i16);
-impl_safe_serialize!(i32, serialize_i32);
-impl_safe_serialize!(i64, serialize_i64);
-impl_safe_serialize!(i128, serialize_i128);
-impl_safe_serialize!(isize, serialize_i64 as i64);
+/// Integers are fingerprinted as their big endian bytes.
+macro_rules! impl_safe_serialize_int {
+    ($type:ty, $serialize_fn:ident $($cast:tt)*) => {
+        impl SafeSerialize for $type {
+            #[inline]
+            fn safe_serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serializer.$serialize_fn(*self $($cast)*)
+            }
+
+            fn with_redacted_bytes<R, F>(&self, f: F) -> R
+            where
+                F: FnOnce(Option<&[u8]>) -> R,
+            {
+                let mut bytes = self.to_be_bytes();
+                let result = f(Some(&bytes));
+                bytes.zeroize();
+                result
+            }
+        }
+    };
+}
+
+impl_safe_serialize_int!(u8, serialize_u8);
+impl_safe_serialize_int!(u16, serialize_u16);
+impl_safe_serialize_int!(u32, serialize_u32);
+impl_safe_serialize_int!(u64, serialize_u64);
+impl_safe_serialize_int!(u128, serialize_u128);
+impl_safe_serialize_int!(usize, serialize_u64 as u64);
+impl_safe_serialize_int!(i8, serialize_i8);
+impl_safe_serialize_int!(i16, serialize_i16);
+impl_safe_serialize_int!(i32, serialize_i32);
+impl_safe_serialize_int!(i64, serialize_i64);
+impl_safe_serialize_int!(i128, serialize_i128);
+impl_safe_serialize_int!(isize, serialize_i64 as i64);
 impl_safe_serialize!(f32, serialize_f32);
 impl_safe_serialize!(f64, serialize_f64);
 impl_safe_serialize!(char, serialize_char);
 impl_safe_serialize!(bool, serialize_bool);
 
-impl SafeSerialize for String {
+#[cfg(feature = "alloc")]
+impl SafeSerialize for alloc::string::String {
     #[inline]
     fn safe_serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -72,6 +124,17 @@ impl SafeSerialize for String {
     {
         serializer.serialize_str(self)
     }
+
+    fn redacted_len(&self) -> usize {
+        self.len()
+    }
+
+    fn with_redacted_bytes<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(Option<&[u8]>) -> R,
+    {
+        f(Some(self.as_bytes()))
+    }
 }
 
 impl<const N: usize> SafeSerialize for [u8; N] {
@@ -81,6 +144,13 @@ impl<const N: usize> SafeSerialize for [u8; N] {
     {
         serializer.serialize_bytes(self)
     }
+
+    fn with_redacted_bytes<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(Option<&[u8]>) -> R,
+    {
+        f(Some(self))
+    }
 }
 
 macro_rules! impl_safe_serialize_nonzero {
@@ -137,6 +207,11 @@ macro_rules! tuple_impl_body {
             )+
             tuple.end()
         }
+
+        #[inline]
+        fn redacted_len(&self) -> usize {
+            0 $(+ self.$n.redacted_len())+
+        }
     };
 }
 /// This trait is implemented for tuples up to 10 items long (the same as Zeroize).
@@ -0,0 +1,214 @@
+use crate::Controlled;
+use core::fmt::{self, Debug, Display};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+const FINGERPRINT_DOMAIN: &[u8] = b"vitaminc.fingerprint.v1";
+
+/// Length in bytes of a [Fingerprint].
+pub const FINGERPRINT_LEN: usize = 8;
+
+/// A short identifier for a secret value.
+///
+/// Conceptually similar to a key check value (KCV), a fingerprint is a truncated
+/// HMAC-SHA256 of the secret. Use it to tell which key is loaded in a service
+/// without printing the key.
+///
+/// Unkeyed fingerprints ([AsFingerprint::fingerprint]) can be computed by anyone, so a fingerprint of a
+/// low-entropy secret (such as a password or PIN) can be brute forced with a dictionary of guesses.
+/// Only use them for high-entropy secrets like keys and use [AsFingerprint::fingerprint_with_key] for anything else.
+///
+/// Fingerprints are public values so they implement `Display`, `Debug` and `PartialEq`.
+/// Because fingerprints are truncated, they identify secrets but must not be used to authenticate them.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fingerprint([u8; FINGERPRINT_LEN]);
+
+impl Fingerprint {
+    pub fn as_bytes(&self) -> &[u8; FINGERPRINT_LEN] {
+        &self.0
+    }
+
+    /// HMAC-SHA256 of `data` keyed with the domain (for unkeyed fingerprints) or a secret key.
+    /// Keyed fingerprints prefix the data with the domain so they can't collide with other uses of the key.
+    pub(crate) fn compute(key: Option<&[u8; 32]>, data: &[u8]) -> Self {
+        let mut mac = match key {
+            Some(key) => {
+                let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key)
+                    .expect("HMAC accepts keys of any length");
+                mac.update(FINGERPRINT_DOMAIN);
+                mac
+            }
+            None => <Hmac<Sha256> as Mac>::new_from_slice(FINGERPRINT_DOMAIN)
+                .expect("HMAC accepts keys of any length"),
+        };
+        mac.update(data);
+        let tag = mac.finalize().into_bytes();
+        let mut out = [0; FINGERPRINT_LEN];
+        out.copy_from_slice(&tag[..FINGERPRINT_LEN]);
+        Self(out)
+    }
+}
+
+impl Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+impl Debug for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Fingerprint({self})")
+    }
+}
+
+/// Fingerprinting of [Controlled] types whose inner value is `AsRef<[u8]>`.
+///
+/// # Example
+///
+/// ```
+/// use vitaminc_protected::{AsFingerprint, Protected};
+///
+/// let key = Protected::new([0u8; 32]);
+/// let fingerprint = key.fingerprint();
+/// assert_eq!(fingerprint.to_string().len(), 16);
+/// assert_eq!(fingerprint, Protected::new([0u8; 32]).fingerprint());
+///
+/// // Debug output with the fingerprint instead of `{ ... }`
+/// assert_eq!(
+///     format!("{:?}", key.fingerprint_debug()),
+///     format!("Protected {{ fingerprint: {fingerprint}, .. }}")
+/// );
+///
+/// // Low-entropy secrets should only be fingerprinted with a key
+/// let fingerprint_key = Protected::new([7u8; 32]);
+/// let password = Protected::new(String::from("hunter2"));
+/// println!("{}", password.fingerprint_with_key(&fingerprint_key));
+/// ```
+pub trait AsFingerprint {
+    /// A domain separated fingerprint that is stable across processes.
+    ///
+    /// This is not keyed so it must only be used for high-entropy secrets (see [Fingerprint]).
+    fn fingerprint(&self) -> Fingerprint;
+
+    /// A fingerprint keyed with `key` so that it can only be computed by holders of the key.
+    fn fingerprint_with_key<K>(&self, key: &K) -> Fingerprint
+    where
+        K: Controlled<Inner = [u8; 32]>;
+
+    /// Wraps `self` so that its `Debug` output includes the fingerprint instead of `{ ... }`.
+    fn fingerprint_debug(&self) -> FingerprintDebug<'_, Self> {
+        FingerprintDebug(self)
+    }
+}
+
+impl<T> AsFingerprint for T
+where
+    T: Controlled,
+    T::Inner: AsRef<[u8]>,
+{
+    fn fingerprint(&self) -> Fingerprint {
+        self.with_inner(|x| Fingerprint::compute(None, x.as_ref()))
+    }
+
+    fn fingerprint_with_key<K>(&self, key: &K) -> Fingerprint
+    where
+        K: Controlled<Inner = [u8; 32]>,
+    {
+        key.with_inner(|key| self.with_inner(|x| Fingerprint::compute(Some(key), x.as_ref())))
+    }
+}
+
+/// Debug formatter returned by [AsFingerprint::fingerprint_debug].
+///
+/// Prints the outermost type name and the fingerprint, e.g. `Protected { fingerprint: 3ef53d1d4378a9c8, .. }`.
+pub struct FingerprintDebug<'a, T: ?Sized>(&'a T);
+
+impl<T> Debug for FingerprintDebug<'_, T>
+where
+    T: AsFingerprint,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(short_type_name::<T>())
+            .field("fingerprint", &format_args!("{}", self.0.fingerprint()))
+            .finish_non_exhaustive()
+    }
+}
+
+/// The name of `T` without its module path or generic parameters.
+fn short_type_name<T>() -> &'static str {
+    let name = core::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Equatable, Exportable, Protected};
+    use std::{
+        format,
+        string::{String, ToString},
+        vec,
+    };
+
+    #[test]
+    fn test_fingerprint_is_stable() {
+        let a = Protected::new([1u8; 32]);
+        let b: Exportable<Protected<[u8; 32]>> = Exportable::new([1u8; 32]);
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_eq!(a.fingerprint().to_string(), "e61e3b2069ce1150");
+    }
+
+    #[test]
+    fn test_fingerprint_differs() {
+        let a = Protected::new([1u8; 32]);
+        let b = Protected::new([2u8; 32]);
+        assert_ne!(a.fingerprint(), b.fingerprint());
+    }
+
+    #[test]
+    fn test_keyed_fingerprint() {
+        let secret = Protected::new(vec![1u8, 2, 3]);
+        let k1 = Protected::new([1u8; 32]);
+        let k2 = Protected::new([2u8; 32]);
+        assert_eq!(
+            secret.fingerprint_with_key(&k1),
+            secret.fingerprint_with_key(&k1)
+        );
+        assert_ne!(
+            secret.fingerprint_with_key(&k1),
+            secret.fingerprint_with_key(&k2)
+        );
+        assert_ne!(secret.fingerprint_with_key(&k1), secret.fingerprint());
+    }
+
+    #[test]
+    fn test_fingerprint_display_and_debug() {
+        let fingerprint = Protected::new(String::from("secret")).fingerprint();
+        let hex = fingerprint.to_string();
+        assert_eq!(hex.len(), FINGERPRINT_LEN * 2);
+        assert!(hex.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(format!("{fingerprint:?}"), format!("Fingerprint({hex})"));
+    }
+
+    #[test]
+    fn test_fingerprint_debug() {
+        let x: Equatable<Protected<[u8; 16]>> = Equatable::new([0; 16]);
+        assert_eq!(
+            format!("{:?}", x.fingerprint_debug()),
+            format!("Equatable {{ fingerprint: {}, .. }}", x.fingerprint())
+        );
+    }
+
+    #[test]
+    fn test_keyed_fingerprint_is_not_the_unkeyed_fingerprint() {
+        // Keying with the domain must not reproduce the unkeyed fingerprint
+        let mut domain = [0u8; 32];
+        domain[..FINGERPRINT_DOMAIN.len()].copy_from_slice(FINGERPRINT_DOMAIN);
+        let secret = Protected::new([1u8; 32]);
+        assert_ne!(
+            secret.fingerprint_with_key(&Protected::new(domain)),
+            secret.fingerprint()
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Process hardening for services that handle secrets (Linux only).
+//!
+//! Protecting individual values doesn't help if the whole process can be core dumped
+//! or attached to with a debugger. With the `hardening` feature enabled, call [harden]
+//! once at startup (before loading any secrets) to:
+//!
+//! * Set the `RLIMIT_CORE` resource limit to zero so no core dumps are written
+//! * Mark the process as not dumpable (`PR_SET_DUMPABLE`) which also prevents
+//!   unprivileged processes from attaching to it with `ptrace`
+//! * Check that the process isn't already being traced
+//!
+//! [report] returns the current state without changing anything.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use vitaminc_protected::{hardening, Protected};
+//!
+//! let report = hardening::harden()?;
+//! assert!(report.is_hardened());
+//! eprintln!("ptrace scope: {:?}", report.ptrace_scope);
+//!
+//! let _key = Protected::<Vec<u8>>::from_file("/run/secrets/key").expect("key");
+//! # Ok::<(), hardening::HardeningError>(())
+//! ```
+use std::{fs, io};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum HardeningError {
+    #[error("Process is being traced by pid {0}")]
+    Traced(u32),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// The system-wide Yama ptrace policy (`/proc/sys/kernel/yama/ptrace_scope`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PtraceScope {
+    /// Any process can attach to another process with the same uid.
+    Classic,
+    /// Only descendants (or processes allowed with `PR_SET_PTRACER`) can be attached to.
+    Restricted,
+    /// Only processes with `CAP_SYS_PTRACE` can attach.
+    AdminOnly,
+    /// No process can attach.
+    Disabled,
+}
+
+/// The hardening state of the current process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HardeningReport {
+    /// The soft `RLIMIT_CORE` limit in bytes (core dumps are disabled when this is zero).
+    pub core_limit: u64,
+    /// Whether the process can be core dumped or attached to by unprivileged processes.
+    pub dumpable: bool,
+    /// The pid of the process tracing this one, if any.
+    pub tracer_pid: Option<u32>,
+    /// The Yama ptrace policy, if the Yama LSM is enabled.
+    pub ptrace_scope: Option<PtraceScope>,
+}
+
+impl HardeningReport {
+    /// Returns `true` if core dumps are disabled, the process isn't dumpable and it isn't being traced.
+    pub fn is_hardened(&self) -> bool {
+        self.core_limit == 0 && !self.dumpable && self.tracer_pid.is_none()
+    }
+}
+
+/// Disable core dumps and ptrace attachment for the current process and return the resulting state.
+///
+/// Returns [HardeningError::Traced] if the process is already being traced
+/// (the other settings are still applied).
+pub fn harden() -> Result<HardeningReport, HardeningError> {
+    let limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::setrlimit(libc::RLIMIT_CORE, &limit) } != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    if unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 0, 0, 0, 0) } != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+
+    let report = report()?;
+    match report.tracer_pid {
+        Some(pid) => Err(HardeningError::Traced(pid)),
+        None => Ok(report),
+    }
+}
+
+/// Report the hardening state of the current process without changing it.
+pub fn report() -> io::Result<HardeningReport> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::getrlimit(libc::RLIMIT_CORE, &mut limit) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let dumpable = unsafe { libc::prctl(libc::PR_GET_DUMPABLE, 0, 0, 0, 0) };
+    if dumpable < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // `rlim_t` is 32 bits on some targets
+    #[allow(clippy::unnecessary_cast)]
+    let core_limit = limit.rlim_cur as u64;
+
+    Ok(HardeningReport {
+        core_limit,
+        dumpable: dumpable != 0,
+        tracer_pid: parse_tracer_pid(&fs::read_to_string("/proc/self/status")?),
+        ptrace_scope: fs::read_to_string("/proc/sys/kernel/yama/ptrace_scope")
+            .ok()
+            .and_then(|s| parse_ptrace_scope(&s)),
+    })
+}
+
+fn parse_tracer_pid(status: &str) -> Option<u32> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("TracerPid:"))
+        .and_then(|pid| pid.trim().parse().ok())
+        .filter(|pid| *pid != 0)
+}
+
+fn parse_ptrace_scope(value: &str) -> Option<PtraceScope> {
+    match value.trim() {
+        "0" => Some(PtraceScope::Classic),
+        "1" => Some(PtraceScope::Restricted),
+        "2" => Some(PtraceScope::AdminOnly),
+        "3" => Some(PtraceScope::Disabled),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tracer_pid() {
+        assert_eq!(parse_tracer_pid("Name:\ttest\nTracerPid:\t0\n"), None);
+        assert_eq!(
+            parse_tracer_pid("Name:\ttest\nTracerPid:\t1234\n"),
+            Some(1234)
+        );
+        assert_eq!(parse_tracer_pid("Name:\ttest\n"), None);
+    }
+
+    #[test]
+    fn test_parse_ptrace_scope() {
+        assert_eq!(parse_ptrace_scope("1\n"), Some(PtraceScope::Restricted));
+        assert_eq!(parse_ptrace_scope("3"), Some(PtraceScope::Disabled));
+        assert_eq!(parse_ptrace_scope("7"), None);
+    }
+}
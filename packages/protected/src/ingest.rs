@@ -0,0 +1,335 @@
+//! Constructors that read secrets from the environment, files and the terminal
+//! directly into [Protected] values.
+//!
+//! Reading a secret with the standard library usually leaves copies behind: `read_to_end` grows
+//! (and reallocates) its buffer, error values carry the bytes that failed to decode and
+//! intermediate `String`s are dropped without being zeroized. The constructors in this module
+//! allocate their buffer once and zeroize anything that does not end up in the returned value.
+use crate::Protected;
+use std::{
+    env,
+    ffi::OsString,
+    fs::File,
+    io::{self, Read, Write},
+    path::Path,
+};
+use thiserror::Error;
+use zeroize::Zeroize;
+
+/// Maximum length of a line read by `Protected::<String>::from_stdin`.
+pub const MAX_LINE_LEN: usize = 1024;
+
+#[derive(Error, Debug)]
+pub enum IngestError {
+    #[error("Environment variable `{0}` is not set")]
+    NotPresent(String),
+    #[error("Secret is not valid UTF-8")]
+    InvalidUtf8,
+    #[error("File is accessible by group or others (mode {0:o})")]
+    InsecurePermissions(u32),
+    #[error("File changed while it was being read")]
+    FileChanged,
+    #[error("Line is longer than {MAX_LINE_LEN} bytes")]
+    TooLong,
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl Protected<String> {
+    /// Read the environment variable `name`.
+    ///
+    /// ```
+    /// use vitaminc_protected::{Controlled, Protected};
+    ///
+    /// std::env::set_var("DOCTEST_API_TOKEN", "s3cret");
+    /// let token = Protected::<String>::from_env("DOCTEST_API_TOKEN").unwrap();
+    /// assert_eq!(token.risky_unwrap(), "s3cret");
+    /// ```
+    pub fn from_env(name: &str) -> Result<Self, IngestError> {
+        let value = env::var_os(name).ok_or_else(|| IngestError::NotPresent(name.to_string()))?;
+        os_string_into_protected(value)
+    }
+
+    /// Read the environment variable `name` and remove it from the environment of the current
+    /// process so that it is not inherited by child processes.
+    ///
+    /// Note that the process environment is owned by the platform and is not zeroized when the
+    /// variable is removed.
+    pub fn take_env(name: &str) -> Result<Self, IngestError> {
+        let value = Self::from_env(name)?;
+        env::remove_var(name);
+        Ok(value)
+    }
+
+    /// Read the contents of a file as UTF-8. See `Protected::<Vec<u8>>::from_file`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, IngestError> {
+        Protected::<Vec<u8>>::from_file(path).and_then(|bytes| bytes_into_string(bytes.0))
+    }
+
+    /// Prompt on stderr and read a line from stdin.
+    /// Terminal echo is disabled while reading if stdin is a terminal.
+    /// The trailing newline is not included.
+    #[cfg(unix)]
+    pub fn from_stdin(prompt: &str) -> Result<Self, IngestError> {
+        let mut stderr = io::stderr();
+        stderr.write_all(prompt.as_bytes())?;
+        stderr.flush()?;
+
+        let line = {
+            use std::os::unix::io::FromRawFd;
+            let _echo = EchoGuard::disable(libc::STDIN_FILENO)?;
+            // Read from the file descriptor directly, bypassing the (never zeroized) buffer in `Stdin`
+            let stdin =
+                std::mem::ManuallyDrop::new(unsafe { File::from_raw_fd(libc::STDIN_FILENO) });
+            read_line(&*stdin)
+        };
+        if unsafe { libc::isatty(libc::STDIN_FILENO) } == 1 {
+            // The newline typed by the user was not echoed
+            stderr.write_all(b"\n")?;
+        }
+        line.and_then(|bytes| bytes_into_string(bytes.0))
+    }
+}
+
+impl Protected<Vec<u8>> {
+    /// Read the contents of a file.
+    ///
+    /// On Unix, the file must not be readable or writable by group or others.
+    /// The buffer is allocated once with the size of the file; if the file grows while being
+    /// read [IngestError::FileChanged] is returned.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, IngestError> {
+        let mut file = File::open(path)?;
+        let metadata = file.metadata()?;
+        check_permissions(&metadata)?;
+
+        let len = usize::try_from(metadata.len()).map_err(|_| IngestError::FileChanged)?;
+        let mut buffer = Protected::new(vec![0; len]);
+        let mut filled = 0;
+        while filled < len {
+            match file.read(&mut buffer.0[filled..]) {
+                Ok(0) => {
+                    buffer.0.zeroize();
+                    return Err(IngestError::FileChanged);
+                }
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    buffer.0.zeroize();
+                    return Err(e.into());
+                }
+            }
+        }
+
+        let mut probe = [0u8; 1];
+        let grown = file.read(&mut probe);
+        probe.zeroize();
+        if !matches!(grown, Ok(0)) {
+            buffer.0.zeroize();
+            return Err(grown
+                .err()
+                .map_or(IngestError::FileChanged, IngestError::from));
+        }
+        Ok(buffer)
+    }
+}
+
+#[cfg(unix)]
+fn check_permissions(metadata: &std::fs::Metadata) -> Result<(), IngestError> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = metadata.permissions().mode();
+    if mode & 0o077 != 0 {
+        return Err(IngestError::InsecurePermissions(mode & 0o777));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_: &std::fs::Metadata) -> Result<(), IngestError> {
+    Ok(())
+}
+
+fn os_string_into_protected(value: OsString) -> Result<Protected<String>, IngestError> {
+    value.into_string().map(Protected::new).map_err(|value| {
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStringExt;
+            value.into_vec().zeroize();
+        }
+        #[cfg(not(unix))]
+        drop(value);
+        IngestError::InvalidUtf8
+    })
+}
+
+/// Convert without copying, zeroizing the bytes if they aren't valid UTF-8.
+fn bytes_into_string(bytes: Vec<u8>) -> Result<Protected<String>, IngestError> {
+    String::from_utf8(bytes).map(Protected::new).map_err(|e| {
+        e.into_bytes().zeroize();
+        IngestError::InvalidUtf8
+    })
+}
+
+/// Read bytes up to a newline (or EOF) into a buffer allocated once with [MAX_LINE_LEN] capacity.
+fn read_line<R: Read>(mut reader: R) -> Result<Protected<Vec<u8>>, IngestError> {
+    let mut line = Protected::new(Vec::with_capacity(MAX_LINE_LEN));
+    let mut byte = [0u8; 1];
+    let result = loop {
+        match reader.read(&mut byte) {
+            Ok(0) => break Ok(()),
+            Ok(_) if byte[0] == b'\n' => break Ok(()),
+            Ok(_) if line.0.len() == MAX_LINE_LEN => break Err(IngestError::TooLong),
+            Ok(_) => line.0.push(byte[0]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => break Err(e.into()),
+        }
+    };
+    byte.zeroize();
+
+    if line.0.last() == Some(&b'\r') {
+        line.0.pop();
+        line.0.spare_capacity_mut()[0].write(0);
+    }
+    match result {
+        Ok(()) => Ok(line),
+        Err(e) => {
+            line.0.zeroize();
+            Err(e)
+        }
+    }
+}
+
+/// Disables terminal echo on a file descriptor and restores it when dropped.
+#[cfg(unix)]
+struct EchoGuard {
+    fd: libc::c_int,
+    original: Option<libc::termios>,
+}
+
+#[cfg(unix)]
+impl EchoGuard {
+    fn disable(fd: libc::c_int) -> io::Result<Self> {
+        if unsafe { libc::isatty(fd) } != 1 {
+            return Ok(Self { fd, original: None });
+        }
+        let mut termios = std::mem::MaybeUninit::<libc::termios>::uninit();
+        if unsafe { libc::tcgetattr(fd, termios.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let original = unsafe { termios.assume_init() };
+        let mut silent = original;
+        silent.c_lflag &= !libc::ECHO;
+        if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &silent) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self {
+            fd,
+            original: Some(original),
+        })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for EchoGuard {
+    fn drop(&mut self) {
+        if let Some(original) = &self.original {
+            unsafe { libc::tcsetattr(self.fd, libc::TCSANOW, original) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Controlled;
+    use std::io::Cursor;
+
+    fn temp_file(name: &str, contents: &[u8], mode: u32) -> std::path::PathBuf {
+        let path = env::temp_dir().join(format!("vitaminc-ingest-{}-{name}", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
+        path
+    }
+
+    #[test]
+    fn test_from_env() {
+        env::set_var("VITAMINC_TEST_FROM_ENV", "secret");
+        let x = Protected::<String>::from_env("VITAMINC_TEST_FROM_ENV").unwrap();
+        assert_eq!(x.risky_unwrap(), "secret");
+        assert!(env::var_os("VITAMINC_TEST_FROM_ENV").is_some());
+    }
+
+    #[test]
+    fn test_take_env() {
+        env::set_var("VITAMINC_TEST_TAKE_ENV", "secret");
+        let x = Protected::<String>::take_env("VITAMINC_TEST_TAKE_ENV").unwrap();
+        assert_eq!(x.risky_unwrap(), "secret");
+        assert!(env::var_os("VITAMINC_TEST_TAKE_ENV").is_none());
+    }
+
+    #[test]
+    fn test_env_not_present() {
+        assert!(matches!(
+            Protected::<String>::from_env("VITAMINC_TEST_NOT_SET"),
+            Err(IngestError::NotPresent(name)) if name == "VITAMINC_TEST_NOT_SET"
+        ));
+    }
+
+    #[test]
+    fn test_from_file() {
+        let path = temp_file("ok", b"secret bytes", 0o600);
+        let bytes = Protected::<Vec<u8>>::from_file(&path).unwrap();
+        assert_eq!(bytes.risky_unwrap(), b"secret bytes");
+        let string = Protected::<String>::from_file(&path).unwrap();
+        assert_eq!(string.risky_unwrap(), "secret bytes");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_from_file_invalid_utf8() {
+        let path = temp_file("utf8", &[0xff, 0xfe], 0o600);
+        assert!(matches!(
+            Protected::<String>::from_file(&path),
+            Err(IngestError::InvalidUtf8)
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_from_file_insecure_permissions() {
+        let path = temp_file("perms", b"secret", 0o644);
+        assert!(matches!(
+            Protected::<Vec<u8>>::from_file(&path),
+            Err(IngestError::InsecurePermissions(0o644))
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_read_line() {
+        let line = read_line(Cursor::new(b"hunter2\r\nnext line")).unwrap();
+        assert_eq!(line.risky_unwrap(), b"hunter2");
+        let line = read_line(Cursor::new(b"no newline")).unwrap();
+        assert_eq!(line.risky_unwrap(), b"no newline");
+    }
+
+    #[test]
+    fn test_read_line_too_long() {
+        let input = vec![b'a'; MAX_LINE_LEN + 1];
+        assert!(matches!(
+            read_line(Cursor::new(input)),
+            Err(IngestError::TooLong)
+        ));
+        let input = vec![b'a'; MAX_LINE_LEN];
+        assert_eq!(
+            read_line(Cursor::new(input)).unwrap().risky_unwrap().len(),
+            MAX_LINE_LEN
+        );
+    }
+}
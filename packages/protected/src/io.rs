@@ -0,0 +1,216 @@
+//! `std::io` integration for streaming secrets.
+use crate::{
+    private::ControlledPrivate, Controlled, DefaultScope, Protected, ProtectedDigest, ProtectedRef,
+};
+use digest::Digest;
+use std::io::{self, Read, Write};
+use zeroize::Zeroize;
+
+const STREAM_BUFFER_LEN: usize = 4096;
+
+/// Extension trait for readers to read directly into [Controlled] buffers.
+///
+/// # Example
+///
+/// ```
+/// use vitaminc_protected::{Controlled, Protected, ProtectedRead};
+///
+/// let mut stream: &[u8] = &[1, 2, 3, 4, 5];
+/// let mut key = Protected::new([0u8; 4]);
+/// stream.read_exact_protected(&mut key).unwrap();
+/// assert_eq!(key.risky_unwrap(), [1, 2, 3, 4]);
+/// ```
+pub trait ProtectedRead: Read {
+    /// Fill `buf` exactly, like `Read::read_exact`.
+    /// If the reader fails or ends early, `buf` is zeroized so no partial secret is left behind.
+    /// Fails with [io::ErrorKind::PermissionDenied] if `buf` no longer allows access (e.g. a used [crate::Ephemeral]).
+    fn read_exact_protected<T>(&mut self, buf: &mut T) -> io::Result<()>
+    where
+        T: Controlled,
+        T::Inner: AsMut<[u8]>,
+    {
+        buf.try_with_inner_mut(|inner| {
+            let inner = inner.as_mut();
+            self.read_exact(inner).inspect_err(|_| inner.zeroize())
+        })
+        .map_err(|e| io::Error::new(io::ErrorKind::PermissionDenied, e))?
+    }
+
+    /// Read exactly `N` bytes into a new [Protected] array.
+    fn read_protected_array<const N: usize>(&mut self) -> io::Result<Protected<[u8; N]>> {
+        let mut buf = Protected::new([0; N]);
+        self.read_exact_protected(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+impl<R: Read + ?Sized> ProtectedRead for R {}
+
+/// A writer that only accepts protected data.
+///
+/// `ProtectedWriter` does not implement `Write` so unprotected buffers can't be written to it by accident.
+///
+/// # Example
+///
+/// ```
+/// use vitaminc_protected::{AsProtectedRef, Protected, ProtectedWriter};
+///
+/// let secret = Protected::new(vec![1u8, 2, 3]);
+/// let mut writer = ProtectedWriter::new(Vec::new());
+/// writer.write_protected(secret.as_protected_ref()).unwrap();
+/// assert_eq!(writer.into_inner(), vec![1, 2, 3]);
+/// ```
+pub struct ProtectedWriter<W>(W);
+
+impl<W: Write> ProtectedWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self(writer)
+    }
+
+    /// Write all of `data`, like `Write::write_all`.
+    pub fn write_protected(&mut self, data: ProtectedRef<'_, [u8]>) -> io::Result<()> {
+        self.0.write_all(data.inner_ref())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> W {
+        self.0
+    }
+}
+
+/// Hash a stream into a protected digest, for example with `io::copy`.
+/// Only digests with the [DefaultScope] accept unscoped input.
+///
+/// Note that `io::copy` reads through its own buffer which is not zeroized.
+/// Use [ProtectedDigest::update_reader] to stream through a buffer that is zeroized after use.
+///
+/// ```
+/// use std::io;
+/// use vitaminc_protected::{Controlled, Protected, ProtectedDigest};
+/// use sha2::Sha256;
+///
+/// let mut digest: ProtectedDigest<Sha256> = ProtectedDigest::new();
+/// io::copy(&mut &b"streamed secret"[..], &mut digest).unwrap();
+/// let hash: Protected<[u8; 32]> = digest.finalize();
+/// ```
+impl<D: Digest> Write for ProtectedDigest<D, DefaultScope> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Digest::update(&mut self.0, buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<D: Digest> ProtectedDigest<D, DefaultScope> {
+    /// Hash everything from `reader` until EOF, returning the number of bytes read.
+    /// The intermediate buffer is zeroized before returning.
+    pub fn update_reader<R: Read + ?Sized>(&mut self, reader: &mut R) -> io::Result<u64> {
+        let mut buf = Protected::new([0u8; STREAM_BUFFER_LEN]);
+        let mut total = 0;
+        let result = loop {
+            match reader.read(buf.inner_mut()) {
+                Ok(0) => break Ok(total),
+                Ok(n) => {
+                    Digest::update(&mut self.0, &buf.inner()[..n]);
+                    total += n as u64;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => break Err(e),
+            }
+        };
+        buf.zeroize();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AsProtectedRef;
+    use sha2::Sha256;
+
+    #[test]
+    fn test_read_exact_protected_vec() {
+        let mut reader: &[u8] = &[1, 2, 3, 4];
+        let mut buf = Protected::new(vec![0u8; 3]);
+        reader.read_exact_protected(&mut buf).unwrap();
+        assert_eq!(buf.risky_unwrap(), vec![1, 2, 3]);
+        assert_eq!(reader, &[4]);
+    }
+
+    #[test]
+    fn test_read_exact_protected_short() {
+        let mut reader: &[u8] = &[1, 2];
+        let mut buf = Protected::new([9u8; 4]);
+        let err = reader.read_exact_protected(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(buf.risky_unwrap(), [0; 4]);
+    }
+
+    #[test]
+    fn test_read_exact_masked() {
+        let mut reader: &[u8] = &[1, 2, 3, 4];
+        let mut buf = crate::Masked::new([0u8; 4]);
+        reader.read_exact_protected(&mut buf).unwrap();
+        assert_eq!(buf.risky_unwrap(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_read_exact_used_ephemeral() {
+        let mut reader: &[u8] = &[1, 2, 3, 4];
+        let mut buf: crate::Ephemeral<Protected<[u8; 2]>> = crate::Ephemeral::new([0; 2]);
+        reader.read_exact_protected(&mut buf).unwrap();
+        let err = reader.read_exact_protected(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(reader, &[3, 4]);
+    }
+
+    #[test]
+    fn test_read_protected_array() {
+        let mut reader: &[u8] = &[7; 16];
+        let key: Protected<[u8; 16]> = reader.read_protected_array().unwrap();
+        assert_eq!(key.risky_unwrap(), [7; 16]);
+    }
+
+    #[test]
+    fn test_protected_writer() {
+        let a = Protected::new([1u8, 2]);
+        let b = Protected::new(vec![3u8]);
+        let mut writer = ProtectedWriter::new(Vec::new());
+        writer.write_protected(a.as_protected_ref()).unwrap();
+        writer.write_protected(b.as_protected_ref()).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.get_ref(), &vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_digest_io_copy() {
+        let data = vec![42u8; STREAM_BUFFER_LEN * 3 + 1];
+
+        let mut copied: ProtectedDigest<Sha256> = ProtectedDigest::new();
+        io::copy(&mut data.as_slice(), &mut copied).unwrap();
+        let copied: Protected<[u8; 32]> = copied.finalize();
+
+        let mut streamed: ProtectedDigest<Sha256> = ProtectedDigest::new();
+        assert_eq!(
+            streamed.update_reader(&mut data.as_slice()).unwrap(),
+            data.len() as u64
+        );
+        let streamed: Protected<[u8; 32]> = streamed.finalize();
+
+        let expected: Protected<[u8; 32]> =
+            ProtectedDigest::<Sha256>::digest(&Protected::new(data));
+        assert_eq!(copied.risky_unwrap(), expected.risky_unwrap());
+        assert_eq!(streamed.risky_unwrap(), expected.risky_unwrap());
+    }
+}
@@ -1,29 +1,112 @@
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+// Unit tests run on the host, so they can use `std` even when the crate is `no_std`
+#[cfg(test)]
+extern crate std;
+
+mod access;
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
 mod as_protected_ref;
+#[cfg(feature = "audit")]
+pub mod audit;
 mod controlled;
 mod conversions;
 mod digest;
+#[cfg(feature = "alloc")]
+mod envelope;
+mod ephemeral;
 mod equatable;
+#[cfg(feature = "std")]
+mod expiring;
 mod exportable;
+#[cfg(feature = "fingerprint")]
+mod fingerprint;
+#[cfg(all(feature = "hardening", target_os = "linux"))]
+pub mod hardening;
+#[cfg(feature = "std")]
+mod ingest;
+#[cfg(feature = "std")]
+mod io;
+#[cfg(feature = "std")]
+mod limited;
+#[cfg(feature = "std")]
+mod masked;
 mod ops;
+#[cfg(feature = "alloc")]
+mod pinned;
+#[cfg(feature = "proptest")]
+pub mod proptest;
 mod protected;
+mod redacted;
+#[cfg(all(feature = "memfd-secret", target_os = "linux"))]
+mod secret_buffer;
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "timing-check")]
+pub mod timing_check;
 mod usage;
 mod zeroed;
+#[cfg(feature = "zeroize-check")]
+pub mod zeroize_check;
 
+#[cfg(feature = "bitvec")]
+mod bits;
 #[cfg(feature = "bitvec")]
 pub mod bitvec;
 
+#[cfg(feature = "enclave")]
+mod enclave;
+
+#[cfg(feature = "sealed")]
+mod sealed;
+
 pub mod slice_index;
 
 pub use as_protected_ref::{AsProtectedRef, ProtectedRef};
 pub use zeroed::Zeroed;
 
 // Exports
+pub use access::AccessError;
+#[cfg(feature = "bitvec")]
+pub use bits::ProtectedBits;
 pub use controlled::Controlled;
 pub use digest::ProtectedDigest;
+#[cfg(feature = "enclave")]
+pub use enclave::Enclave;
+#[cfg(feature = "alloc")]
+pub use envelope::{Envelope, EnvelopeError, EnvelopePayload, TypeIdentifier, ENVELOPE_VERSION};
+pub use ephemeral::Ephemeral;
 pub use equatable::{ConstantTimeEq, Equatable};
+#[cfg(feature = "std")]
+pub use expiring::Expiring;
 pub use exportable::Exportable;
+#[cfg(feature = "fingerprint")]
+pub use fingerprint::{AsFingerprint, Fingerprint, FingerprintDebug, FINGERPRINT_LEN};
+#[cfg(feature = "std")]
+pub use ingest::{IngestError, MAX_LINE_LEN};
+#[cfg(feature = "std")]
+pub use io::{ProtectedRead, ProtectedWriter};
+#[cfg(feature = "std")]
+pub use limited::Limited;
+#[cfg(feature = "std")]
+pub use masked::Masked;
+#[cfg(feature = "alloc")]
+pub use pinned::{Pinned, ZeroValid};
 pub use protected::{flatten_array, Protected};
+pub use redacted::{
+    redacted, serialize_redacted, serialize_redacted_len, Redacted, RedactedLen, Redaction,
+    REDACTED,
+};
+#[cfg(feature = "sealed")]
+pub use sealed::{SealError, Sealed};
+#[cfg(all(feature = "memfd-secret", target_os = "linux"))]
+pub use secret_buffer::{SecretBuffer, SecretBufferBackend};
+#[cfg(feature = "std")]
+pub use shared::SharedProtected;
 pub use usage::{Acceptable, DefaultScope, Scope, Usage};
 use zeroize::Zeroize;
 
@@ -54,6 +137,26 @@ where
     type Output = Equatable<Exportable<Protected<K>>>;
 }
 
+#[cfg(feature = "std")]
+impl<T, K> ReplaceT<K> for Masked<T>
+where
+    T: Zeroize,
+    K: Zeroize,
+    Masked<K>: Controlled,
+{
+    type Output = Masked<K>;
+}
+
+#[cfg(feature = "alloc")]
+impl<T, K> ReplaceT<K> for Pinned<T>
+where
+    T: Zeroize,
+    K: Zeroize,
+    Pinned<K>: Controlled,
+{
+    type Output = Pinned<K>;
+}
+
 impl<T, K> ReplaceT<K> for Exportable<Protected<T>>
 where
     K: Zeroize,
@@ -69,13 +172,39 @@ where
 }
 
 mod private {
-    use crate::{Equatable, Exportable, Protected};
+    use crate::{AccessError, Equatable, Exportable, Protected};
 
     pub trait Sealed {}
     impl<T> Sealed for Protected<T> {}
+    #[cfg(feature = "std")]
+    impl<T: zeroize::Zeroize> Sealed for crate::Masked<T> {}
+    #[cfg(feature = "alloc")]
+    impl<T: zeroize::Zeroize> Sealed for crate::Pinned<T> {}
     impl<T> Sealed for Equatable<T> {}
     impl<T> Sealed for Exportable<T> {}
 
+    /// Marker for adapter stacks that contain an [Exportable].
+    #[cfg(feature = "alloc")]
+    pub trait Exported {}
+    #[cfg(feature = "alloc")]
+    impl<T> Exported for Exportable<T> {}
+    #[cfg(feature = "alloc")]
+    impl<T: Exported> Exported for Equatable<T> {}
+
+    /// Marker for controlled types that can lend out a reference to their inner value for longer than a closure call.
+    /// [crate::Masked], [crate::Ephemeral] and [crate::Expiring] only lend their inner value to closures
+    /// (see [ControlledPrivate::with_inner]) so APIs that return references (e.g. [crate::AsProtectedRef])
+    /// aren't available for them.
+    pub trait InnerRef {}
+    impl<T> InnerRef for Protected<T> {}
+    #[cfg(feature = "alloc")]
+    impl<T: zeroize::Zeroize> InnerRef for crate::Pinned<T> {}
+    impl<T: InnerRef> InnerRef for Equatable<T> {}
+    impl<T: InnerRef> InnerRef for Exportable<T> {}
+    impl<T: InnerRef, S> InnerRef for crate::Usage<T, S> {}
+    #[cfg(feature = "std")]
+    impl<T: InnerRef, const BUDGET: u64> InnerRef for crate::Limited<T, BUDGET> {}
+
     /// Private trait that is used to hide the inner value of a Controlled type
     /// as well as preventing consumers from implementing Controlled themselves.
     pub trait ControlledPrivate {
@@ -85,5 +214,46 @@ mod private {
         fn init_from_inner(x: Self::Inner) -> Self;
         fn inner(&self) -> &Self::Inner;
         fn inner_mut(&mut self) -> &mut Self::Inner;
+        fn into_inner(self) -> Self::Inner;
+
+        /// Call a closure with a reference to the inner value.
+        /// Types that can't lend out a reference beyond a single call (e.g. [crate::Masked]) override this
+        /// and the closure based methods of [crate::Controlled] use it instead of `inner`.
+        fn with_inner<R, F>(&self, f: F) -> R
+        where
+            F: FnOnce(&Self::Inner) -> R,
+        {
+            f(self.inner())
+        }
+
+        /// Like `with_inner` but returns an error instead of panicking when an adapter
+        /// (e.g. [crate::Ephemeral]) doesn't allow any more access to the inner value.
+        fn try_with_inner<R, F>(&self, f: F) -> Result<R, AccessError>
+        where
+            F: FnOnce(&Self::Inner) -> R,
+        {
+            Ok(self.with_inner(f))
+        }
+
+        /// Like `with_inner` but with a mutable reference.
+        fn with_inner_mut<R, F>(&mut self, f: F) -> R
+        where
+            F: FnOnce(&mut Self::Inner) -> R,
+        {
+            f(self.inner_mut())
+        }
+
+        /// Like `with_inner_mut` but returns an error instead of panicking.
+        fn try_with_inner_mut<R, F>(&mut self, f: F) -> Result<R, AccessError>
+        where
+            F: FnOnce(&mut Self::Inner) -> R,
+        {
+            Ok(self.with_inner_mut(f))
+        }
+
+        /// The name of the usage scope, if any (used for auditing).
+        fn scope_name() -> Option<&'static str> {
+            None
+        }
     }
 }
@@ -0,0 +1,290 @@
+use crate::{private::ControlledPrivate, AccessError, Controlled};
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+use zeroize::Zeroize;
+
+type Rotation = Box<dyn FnOnce() + Send>;
+
+/// A _controlled_ wrapper that limits how many times a key can be used.
+///
+/// Every access to the inner value through the controlled API (e.g. [Controlled::expose], [Controlled::update],
+/// [crate::Equatable] comparisons or [Limited::try_expose]) uses one unit of the budget.
+/// Once the budget is exhausted, `try_expose` returns [AccessError::BudgetExhausted], [crate::Equatable] comparisons
+/// return `false` and other methods panic.
+///
+/// A rotation callback set with [Limited::on_exhausted] is called (once) when the last unit of the budget is used,
+/// so a replacement key can be provisioned before any operation fails.
+///
+/// The counter is atomic so a [Limited] key can be shared between threads.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+/// use vitaminc_protected::{AccessError, Controlled, Limited, Protected};
+///
+/// let rotated = Arc::new(AtomicBool::new(false));
+/// let flag = rotated.clone();
+///
+/// // e.g. 2^32 for AES-GCM with random nonces
+/// let key: Limited<Protected<[u8; 32]>, 2> = Limited::new([0; 32])
+///     .on_exhausted(move || flag.store(true, Ordering::SeqCst));
+///
+/// assert!(key.try_expose(|_| ()).is_ok());
+/// assert!(key.try_expose(|_| ()).is_ok());
+/// assert!(rotated.load(Ordering::SeqCst));
+/// assert_eq!(key.try_expose(|_| ()), Err(AccessError::BudgetExhausted));
+/// ```
+#[derive(Zeroize)]
+pub struct Limited<T, const BUDGET: u64> {
+    inner: T,
+    #[zeroize(skip)]
+    remaining: AtomicU64,
+    #[zeroize(skip)]
+    rotation: Mutex<Option<Rotation>>,
+}
+
+impl<T, const BUDGET: u64> Limited<T, BUDGET> {
+    /// Create a new [Limited] with a budget of `BUDGET` uses.
+    pub fn new(x: <Limited<T, BUDGET> as ControlledPrivate>::Inner) -> Self
+    where
+        Self: ControlledPrivate,
+    {
+        Self::init_from_inner(x)
+    }
+
+    /// Set a callback to be called when the last unit of the budget is used.
+    pub fn on_exhausted<F>(self, f: F) -> Self
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        *self.rotation.lock().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(f));
+        self
+    }
+
+    /// The number of uses remaining.
+    pub fn remaining(&self) -> u64 {
+        self.remaining.load(Ordering::Acquire)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    fn consume(&self) -> Result<(), AccessError> {
+        let previous = self
+            .remaining
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1))
+            .map_err(|_| AccessError::BudgetExhausted)?;
+
+        if previous == 1 {
+            let rotation = self
+                .rotation
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .take();
+            if let Some(rotate) = rotation {
+                rotate();
+            }
+        }
+        Ok(())
+    }
+
+    fn consume_or_panic(&self) {
+        if let Err(e) = self.consume() {
+            panic!("{e}");
+        }
+    }
+}
+
+impl<T, const BUDGET: u64> Limited<T, BUDGET>
+where
+    T: ControlledPrivate + Zeroize,
+{
+    /// Create a new [Limited] with a budget set at runtime instead of `BUDGET`.
+    pub fn with_budget(x: T::Inner, budget: u64) -> Self {
+        Self {
+            inner: T::init_from_inner(x),
+            remaining: AtomicU64::new(budget),
+            rotation: Mutex::new(None),
+        }
+    }
+
+    /// Use the key by passing a reference to a closure, or return an error if the budget is exhausted.
+    #[cfg_attr(feature = "audit", track_caller)]
+    pub fn try_expose<R, F>(&self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&T::Inner) -> R,
+    {
+        let result = self.try_with_inner(f);
+        #[cfg(feature = "audit")]
+        if result.is_ok() {
+            crate::audit::record::<Self>(crate::audit::ExposureKind::Expose);
+        }
+        result
+    }
+}
+
+impl<T, const BUDGET: u64> ControlledPrivate for Limited<T, BUDGET>
+where
+    T: ControlledPrivate + Zeroize,
+{
+    type Inner = T::Inner;
+
+    fn init_from_inner(x: Self::Inner) -> Self {
+        Self::with_budget(x, BUDGET)
+    }
+
+    fn inner(&self) -> &Self::Inner {
+        self.consume_or_panic();
+        self.inner.inner()
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        self.consume_or_panic();
+        self.inner.inner_mut()
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.consume_or_panic();
+        self.inner.into_inner()
+    }
+
+    fn with_inner<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&Self::Inner) -> R,
+    {
+        self.consume_or_panic();
+        self.inner.with_inner(f)
+    }
+
+    fn try_with_inner<R, F>(&self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&Self::Inner) -> R,
+    {
+        self.consume()?;
+        self.inner.try_with_inner(f)
+    }
+
+    fn with_inner_mut<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Self::Inner) -> R,
+    {
+        self.consume_or_panic();
+        self.inner.with_inner_mut(f)
+    }
+
+    fn try_with_inner_mut<R, F>(&mut self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&mut Self::Inner) -> R,
+    {
+        self.consume()?;
+        self.inner.try_with_inner_mut(f)
+    }
+
+    fn scope_name() -> Option<&'static str> {
+        T::scope_name()
+    }
+}
+
+impl<T, const BUDGET: u64> Controlled for Limited<T, BUDGET> where T: Controlled + Zeroize {}
+
+/// Shows the remaining budget next to the wrapped value's own (redacted) `Debug` output.
+impl<T: Debug, const BUDGET: u64> Debug for Limited<T, BUDGET> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Limited")
+            .field("inner", &self.inner)
+            .field("remaining", &self.remaining())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Equatable, Protected};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn test_budget() {
+        let key: Limited<Protected<[u8; 4]>, 3> = Limited::new([1; 4]);
+        assert_eq!(key.remaining(), 3);
+        assert_eq!(key.try_expose(|k| k[0]), Ok(1));
+        key.expose(|_| ());
+        assert_eq!(key.remaining(), 1);
+        assert_eq!(key.try_expose(|k| k[0]), Ok(1));
+        assert!(key.is_exhausted());
+        assert_eq!(key.try_expose(|k| k[0]), Err(AccessError::BudgetExhausted));
+    }
+
+    #[test]
+    #[should_panic(expected = "Secret usage budget is exhausted")]
+    fn test_exhausted_panics() {
+        let mut key: Limited<Protected<u8>, 1> = Limited::new(1);
+        key.update(|x| *x += 1);
+        key.expose(|_| ());
+    }
+
+    #[test]
+    fn test_with_budget() {
+        let key: Limited<Protected<u8>, 0> = Limited::with_budget(42, 1);
+        assert_eq!(key.risky_unwrap(), 42);
+    }
+
+    #[test]
+    fn test_rotation_called_once() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let key: Limited<Protected<u8>, 2> = Limited::new(1).on_exhausted(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        key.expose(|_| ());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        key.expose(|_| ());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(key.try_expose(|_| ()).is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_shared_between_threads() {
+        let key: Arc<Limited<Protected<u8>, 100>> = Arc::new(Limited::new(1));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let key = key.clone();
+                std::thread::spawn(move || {
+                    (0..50).filter(|_| key.try_expose(|_| ()).is_ok()).count()
+                })
+            })
+            .collect();
+        let used: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+        assert_eq!(used, 100);
+        assert!(key.is_exhausted());
+    }
+
+    #[test]
+    fn test_equatable() {
+        let x: Equatable<Limited<Protected<[u8; 4]>, 1>> = Equatable::new([1; 4]);
+        assert!(x.constant_time_eq(&Equatable::new([1; 4])));
+        assert!(!x.constant_time_eq(&Equatable::new([1; 4])));
+        assert_ne!(x, Equatable::<Protected<[u8; 4]>>::new([1; 4]));
+    }
+
+    #[test]
+    fn test_opaque_debug() {
+        let x: Limited<Protected<u8>, 5> = Limited::new(1);
+        assert_eq!(
+            format!("{x:?}"),
+            "Limited { inner: Protected<u8> { ... }, remaining: 5 }"
+        );
+    }
+}
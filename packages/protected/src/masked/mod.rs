@@ -0,0 +1,250 @@
+use crate::{private::ControlledPrivate, Controlled};
+use rand::RngCore;
+use std::cell::RefCell;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// A _controlled_ byte array that is stored as two XOR shares in separate heap allocations.
+///
+/// Neither share on its own reveals anything about the secret, so a single leaked page or allocation
+/// doesn't contain the plaintext. The mask is re-randomized on every access and the plaintext is
+/// only reconstructed, into a temporary buffer that is zeroized afterwards, for the duration of a closure
+/// (e.g. [Controlled::expose], [Controlled::update] or an [crate::Equatable] comparison).
+///
+/// This is much cheaper than [encrypting](https://docs.rs/vitaminc-protected/latest/vitaminc_protected/struct.Enclave.html)
+/// the secret but only protects against partial memory disclosure.
+///
+/// Because there is no plaintext to borrow, methods that return a reference to the inner value
+/// (such as [crate::AsProtectedRef::as_protected_ref] and [Controlled::iter]) aren't available for [Masked] values.
+///
+/// [Masked] values are not `Sync` because the shares are re-randomized through a shared reference.
+///
+/// # Example
+///
+/// ```
+/// use vitaminc_protected::{Controlled, Equatable, Masked};
+///
+/// let mut key: Masked<[u8; 32]> = Masked::new([42; 32]);
+/// key.update(|k| k[0] = 1);
+/// assert_eq!(key.expose(|k| k[0]), 1);
+///
+/// let x: Equatable<Masked<[u8; 4]>> = Equatable::new([1, 2, 3, 4]);
+/// let y: Equatable<Masked<[u8; 4]>> = Equatable::new([1, 2, 3, 4]);
+/// assert_eq!(x, y);
+/// ```
+pub struct Masked<T: Zeroize> {
+    shares: RefCell<[Box<T>; 2]>,
+}
+
+impl<T: Zeroize> std::fmt::Debug for Masked<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Masked<{}> {{ ... }}", std::any::type_name::<T>())
+    }
+}
+
+impl<const N: usize> Masked<[u8; N]> {
+    /// Create a new [Masked] by splitting `x` into two shares.
+    pub fn new(x: [u8; N]) -> Self {
+        Self::init_from_inner(x)
+    }
+
+    fn split(mut x: Zeroizing<[u8; N]>) -> [Box<[u8; N]>; 2] {
+        let mut mask = Box::new([0u8; N]);
+        rand::thread_rng().fill_bytes(mask.as_mut_slice());
+        xor(&mut x, &mask);
+        [Box::new(*x), mask]
+    }
+
+    /// Re-randomize the mask and return a reconstructed copy of the secret.
+    fn reconstruct(&self) -> Zeroizing<[u8; N]> {
+        let mut shares = self.shares.borrow_mut();
+        let mut fresh = Zeroizing::new([0u8; N]);
+        rand::thread_rng().fill_bytes(fresh.as_mut_slice());
+        let [a, b] = &mut *shares;
+        xor(a, &fresh);
+        xor(b, &fresh);
+
+        let mut plaintext = Zeroizing::new(**a);
+        xor(&mut plaintext, b);
+        plaintext
+    }
+}
+
+fn xor<const N: usize>(a: &mut [u8; N], b: &[u8; N]) {
+    a.iter_mut().zip(b.iter()).for_each(|(a, b)| *a ^= b);
+}
+
+impl<T: Zeroize> Zeroize for Masked<T> {
+    fn zeroize(&mut self) {
+        self.shares
+            .get_mut()
+            .iter_mut()
+            .for_each(|share| share.zeroize());
+    }
+}
+
+impl<T: Zeroize> Drop for Masked<T> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<T: Zeroize> ZeroizeOnDrop for Masked<T> {}
+
+impl<const N: usize> ControlledPrivate for Masked<[u8; N]> {
+    type Inner = [u8; N];
+
+    fn init_from_inner(x: Self::Inner) -> Self {
+        Self {
+            shares: RefCell::new(Self::split(Zeroizing::new(x))),
+        }
+    }
+
+    fn inner(&self) -> &Self::Inner {
+        panic!("Masked values can only be accessed inside a closure (e.g. `Controlled::expose`)");
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        panic!(
+            "Masked values can only be accessed inside a closure (e.g. `Controlled::expose_mut`)"
+        );
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        *self.reconstruct()
+    }
+
+    fn with_inner<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&Self::Inner) -> R,
+    {
+        // The shares are no longer borrowed so the closure can access this value again
+        f(&self.reconstruct())
+    }
+
+    fn with_inner_mut<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Self::Inner) -> R,
+    {
+        let mut plaintext = self.reconstruct();
+        let result = f(&mut plaintext);
+        self.zeroize();
+        *self.shares.get_mut() = Self::split(plaintext);
+        result
+    }
+}
+
+impl<const N: usize> Controlled for Masked<[u8; N]> {}
+
+impl<const N: usize> Clone for Masked<[u8; N]> {
+    fn clone(&self) -> Self {
+        Self {
+            shares: RefCell::new(Self::split(self.reconstruct())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Equatable, Exportable, Limited, Protected};
+
+    #[test]
+    fn test_shares_are_remasked_on_access() {
+        let x: Masked<[u8; 16]> = Masked::new([7; 16]);
+        let before = x.shares.borrow()[0].clone();
+        assert_ne!(*before, [7; 16]);
+        assert_eq!(x.expose(|k| *k), [7; 16]);
+        assert_ne!(x.shares.borrow()[0], before);
+    }
+
+    #[test]
+    fn test_update() {
+        let mut x: Masked<[u8; 4]> = Masked::new([1; 4]);
+        x.update(|k| k[1] = 2);
+        x.expose_mut(|k| k[2] = 3);
+        assert_eq!(x.risky_unwrap(), [1, 2, 3, 1]);
+    }
+
+    #[test]
+    fn test_replace_and_clone() {
+        let mut x: Masked<[u8; 4]> = Masked::new([1; 4]);
+        let old = x.replace(Masked::new([2; 4]));
+        assert_eq!(old.risky_unwrap(), [1; 4]);
+        assert_eq!(x.clone().risky_unwrap(), [2; 4]);
+    }
+
+    #[test]
+    fn test_equatable() {
+        let x: Equatable<Masked<[u8; 4]>> = Equatable::new([1; 4]);
+        assert_eq!(x, Equatable::<Masked<[u8; 4]>>::new([1; 4]));
+        assert_ne!(x, Equatable::<Masked<[u8; 4]>>::new([2; 4]));
+        assert_eq!(x, Equatable::<Protected<[u8; 4]>>::new([1; 4]));
+        // Comparing a value to itself reconstructs it twice
+        assert!(x.constant_time_eq(&x));
+    }
+
+    #[test]
+    fn test_adapters() {
+        let x: Exportable<Limited<Masked<[u8; 4]>, 2>> = Exportable::new([1; 4]);
+        assert_eq!(x.expose(|k| k[0]), 1);
+        assert_eq!(serde_json::to_string(&x).unwrap(), "[1,1,1,1]");
+        assert!(x.0.is_exhausted());
+    }
+
+    #[test]
+    fn test_serialize() {
+        use crate::{exportable::SafeSerialize, DefaultScope, Usage};
+        let x: Masked<[u8; 4]> = Masked::new([1; 4]);
+        assert_eq!(x.redacted_len(), 4);
+        let mut json = Vec::new();
+        x.safe_serialize(&mut serde_json::Serializer::new(&mut json))
+            .unwrap();
+        assert_eq!(json, b"[1,1,1,1]");
+
+        let y: Usage<Exportable<Masked<[u8; 4]>>, DefaultScope> = Usage::new([2; 4]);
+        assert_eq!(serde_json::to_string(&y).unwrap(), "[2,2,2,2]");
+    }
+
+    #[test]
+    fn test_envelope() {
+        use crate::{Envelope, Scope, TypeIdentifier, Usage};
+
+        struct KeyScope;
+        impl Scope for KeyScope {}
+        impl TypeIdentifier for KeyScope {
+            const TYPE_ID: &'static str = "test.masked-key";
+        }
+        type Key = Usage<Exportable<Masked<[u8; 4]>>, KeyScope>;
+
+        let bytes = Key::new([3; 4]).to_envelope().unwrap();
+        let key = bytes.expose(|bytes| Key::from_envelope(bytes)).unwrap();
+        assert_eq!(key.risky_unwrap(), [3; 4]);
+    }
+
+    #[test]
+    fn test_digest() {
+        use crate::{DefaultScope, ProtectedDigest, Usage};
+        use sha2::Sha256;
+
+        // Only protected or scoped values are accepted by a digest
+        let x: Usage<Masked<[u8; 4]>, DefaultScope> = Usage::new([1; 4]);
+        let expected: Protected<[u8; 32]> =
+            ProtectedDigest::<Sha256>::digest(&Protected::new([1u8; 4]));
+
+        let mut digest: ProtectedDigest<Sha256> = ProtectedDigest::new();
+        digest.update(&x);
+        let mut out: Masked<[u8; 32]> = Masked::new([0; 32]);
+        digest.finalize_into(&mut out);
+        assert_eq!(out.risky_unwrap(), expected.risky_unwrap());
+
+        let digest: ProtectedDigest<Sha256> = ProtectedDigest::new_with_prefix(&x);
+        let out: Protected<[u8; 32]> = digest.finalize();
+        assert_eq!(out.risky_unwrap(), expected.risky_unwrap());
+    }
+
+    #[test]
+    fn test_opaque_debug() {
+        let x: Masked<[u8; 4]> = Masked::new([1; 4]);
+        assert_eq!(format!("{x:?}"), "Masked<[u8; 4]> { ... }");
+    }
+}
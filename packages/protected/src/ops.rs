@@ -1,4 +1,4 @@
-use std::ops::BitXor;
+use core::ops::BitXor;
 use zeroize::Zeroize;
 
 use crate::{Controlled, Protected};
@@ -0,0 +1,286 @@
+use crate::{private::ControlledPrivate, Controlled, Zeroed};
+use alloc::{
+    alloc::{alloc_zeroed, handle_alloc_error, Layout},
+    boxed::Box,
+};
+use core::ptr::NonNull;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// A _controlled_ value that lives on the heap for its whole lifetime.
+///
+/// Moving a `Protected<[u8; 64]>` (including through [Controlled::map], [Controlled::zip] or [Controlled::replace])
+/// copies the key and leaves copies on the stack that are never zeroized.
+/// Moving a [Pinned] only moves a pointer, and the in-place constructors and combinators
+/// ([Pinned::generate_in_place], [Pinned::map_in_place] and [Pinned::zip_in_place])
+/// write directly into a zeroed heap allocation so large keys never pass through the stack.
+/// The allocation is zeroized when the [Pinned] is dropped.
+///
+/// `Pinned` is only implemented for integers and (nested) arrays of integers,
+/// for which an all-zero allocation is a valid value.
+///
+/// Methods that take or return the inner value by value (such as [Pinned::new], [Controlled::map],
+/// [Controlled::replace] and [Controlled::risky_unwrap]) still copy it through the stack.
+///
+/// # Example
+///
+/// ```
+/// use vitaminc_protected::{Controlled, Equatable, Pinned, Protected};
+///
+/// let key = Pinned::<[u8; 64]>::generate_in_place(|key| key.fill(42));
+///
+/// // Derive a new key without a stack copy of either
+/// let derived = key.map_in_place(|key, out: &mut [u8; 64]| {
+///     out.iter_mut().zip(key).for_each(|(o, k)| *o = k ^ 0xff);
+/// });
+/// assert_eq!(derived.expose(|k| k[0]), 42 ^ 0xff);
+///
+/// let x: Equatable<Pinned<[u8; 64]>> = Equatable::new([1; 64]);
+/// assert_eq!(x, Equatable::<Protected<[u8; 64]>>::new([1; 64]));
+/// ```
+pub struct Pinned<T: Zeroize>(Box<T>);
+
+impl<T: ZeroValid> Pinned<T> {
+    /// Create a new [Pinned] by moving `x` onto the heap.
+    /// Prefer [Pinned::generate_in_place] for large values.
+    pub fn new(x: T) -> Self {
+        Self(Box::new(x))
+    }
+
+    /// Allocate a zeroed value directly on the heap.
+    pub fn zeroed() -> Self {
+        let layout = Layout::new::<T>();
+        if layout.size() == 0 {
+            // SAFETY: a dangling, aligned pointer is a valid `Box` for zero-sized types
+            return Self(unsafe { Box::from_raw(NonNull::<T>::dangling().as_ptr()) });
+        }
+        // SAFETY: `layout` has a non-zero size and `ZeroValid` is only implemented for types
+        // where all zero bytes is a valid value, so the allocation holds an initialized `T`
+        // which is allocated with the global allocator and the layout that `Box` expects
+        unsafe {
+            let ptr = alloc_zeroed(layout).cast::<T>();
+            if ptr.is_null() {
+                handle_alloc_error(layout);
+            }
+            Self(Box::from_raw(ptr))
+        }
+    }
+
+    /// Allocate a zeroed value on the heap and initialize it in place with `f`.
+    pub fn generate_in_place<F>(f: F) -> Self
+    where
+        F: FnOnce(&mut T),
+    {
+        let mut x = Self::zeroed();
+        f(&mut x.0);
+        x
+    }
+
+    /// Like [Pinned::generate_in_place] but `f` can fail.
+    /// The allocation is zeroized if it does.
+    pub fn try_generate_in_place<F, E>(f: F) -> Result<Self, E>
+    where
+        F: FnOnce(&mut T) -> Result<(), E>,
+    {
+        let mut x = Self::zeroed();
+        f(&mut x.0)?;
+        Ok(x)
+    }
+
+    /// Transform the value into a new heap allocation without copying either through the stack.
+    /// `f` is passed a reference to the current value and a zeroed output to write to.
+    /// The current value is zeroized when this returns.
+    pub fn map_in_place<U, F>(self, f: F) -> Pinned<U>
+    where
+        U: ZeroValid,
+        F: FnOnce(&T, &mut U),
+    {
+        Pinned::generate_in_place(|out| f(&self.0, out))
+    }
+
+    /// Like [Pinned::map_in_place] but combines this value with a reference to another [Controlled] value.
+    pub fn zip_in_place<U, Other, F>(self, other: &Other, f: F) -> Pinned<U>
+    where
+        U: ZeroValid,
+        Other: Controlled,
+        F: FnOnce(&T, &Other::Inner, &mut U),
+    {
+        other.with_inner(|other| Pinned::generate_in_place(|out| f(&self.0, other, out)))
+    }
+
+    /// Swap the values of two [Pinned] without copying either.
+    pub fn swap(&mut self, other: &mut Self) {
+        core::mem::swap(&mut self.0, &mut other.0);
+    }
+}
+
+impl<T: Zeroize> Zeroize for Pinned<T> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> Drop for Pinned<T> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<T: Zeroize> ZeroizeOnDrop for Pinned<T> {}
+
+impl<T: ZeroValid> ControlledPrivate for Pinned<T> {
+    type Inner = T;
+
+    fn init_from_inner(x: Self::Inner) -> Self {
+        Self::new(x)
+    }
+
+    fn inner(&self) -> &Self::Inner {
+        &self.0
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.0
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        // The heap copy is zeroized when `self` is dropped
+        *self.0
+    }
+}
+
+impl<T: ZeroValid> Controlled for Pinned<T> {}
+
+impl<T: ZeroValid> Zeroed for Pinned<T> {
+    fn zeroed() -> Self {
+        Pinned::zeroed()
+    }
+}
+
+impl<T: ZeroValid> Clone for Pinned<T> {
+    fn clone(&self) -> Self {
+        // Copy between the two allocations rather than through the stack
+        Self::generate_in_place(|x| {
+            // SAFETY: both pointers come from references to distinct allocations of one `T`
+            unsafe { (x as *mut T).copy_from_nonoverlapping(&*self.0, 1) }
+        })
+    }
+}
+
+impl<T: Zeroize> core::fmt::Debug for Pinned<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Pinned<{}> {{ ... }}", core::any::type_name::<T>())
+    }
+}
+
+/// Types for which a value with all bytes set to zero is valid.
+/// This trait is sealed so it can't be implemented outside of this crate.
+pub trait ZeroValid: private::Sealed + Copy + Zeroize {}
+
+macro_rules! impl_zero_valid {
+    ($($type:ty),+) => {
+        $(
+            impl ZeroValid for $type {}
+            impl private::Sealed for $type {}
+        )+
+    };
+}
+
+impl_zero_valid!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl<T: ZeroValid, const N: usize> ZeroValid for [T; N] {}
+impl<T: ZeroValid, const N: usize> private::Sealed for [T; N] {}
+
+mod private {
+    pub trait Sealed {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Equatable, Protected};
+    use std::format;
+
+    #[test]
+    fn test_zeroed() {
+        let x: Pinned<[[u32; 4]; 8]> = Pinned::zeroed();
+        assert_eq!(x.risky_unwrap(), [[0; 4]; 8]);
+    }
+
+    #[test]
+    fn test_zeroed_zero_sized() {
+        let x: Pinned<[u64; 0]> = Pinned::zeroed();
+        assert_eq!(x.risky_unwrap(), [0u64; 0]);
+    }
+
+    #[test]
+    fn test_clone_copies_between_allocations() {
+        let x: Pinned<[u8; 64]> = Pinned::new([9; 64]);
+        let y = x.clone();
+        assert_ne!(x.expose(|k| k.as_ptr()), y.expose(|k| k.as_ptr()));
+        assert_eq!(y.risky_unwrap(), [9; 64]);
+    }
+
+    #[test]
+    fn test_generate_in_place() {
+        let x = Pinned::<[u8; 4]>::generate_in_place(|x| x.copy_from_slice(&[1, 2, 3, 4]));
+        assert_eq!(x.risky_unwrap(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_try_generate_in_place() {
+        let x = Pinned::<[u8; 4]>::try_generate_in_place(|x| {
+            x[0] = 1;
+            Err("failed")
+        });
+        assert_eq!(x.unwrap_err(), "failed");
+    }
+
+    #[test]
+    fn test_map_in_place() {
+        let x: Pinned<[u8; 4]> = Pinned::new([1, 2, 3, 4]);
+        let y: Pinned<u32> = x.map_in_place(|x, y| *y = u32::from_le_bytes(*x));
+        assert_eq!(y.risky_unwrap(), 0x04030201);
+    }
+
+    #[test]
+    fn test_zip_in_place() {
+        let x: Pinned<[u8; 4]> = Pinned::new([1; 4]);
+        let other = Protected::new([2u8; 4]);
+        let y = x.zip_in_place(&other, |x, o, out: &mut [u8; 4]| {
+            out.iter_mut()
+                .zip(x.iter().zip(o))
+                .for_each(|(out, (x, o))| *out = x + o)
+        });
+        assert_eq!(y.risky_unwrap(), [3; 4]);
+    }
+
+    #[test]
+    fn test_moves_keep_the_allocation() {
+        let x: Pinned<[u8; 64]> = Pinned::new([1; 64]);
+        let ptr = x.expose(|k| k.as_ptr());
+        let moved = std::convert::identity(x);
+        assert_eq!(moved.expose(|k| k.as_ptr()), ptr);
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut x: Pinned<[u8; 4]> = Pinned::new([1; 4]);
+        let mut y: Pinned<[u8; 4]> = Pinned::new([2; 4]);
+        x.swap(&mut y);
+        assert_eq!(x.risky_unwrap(), [2; 4]);
+        assert_eq!(y.risky_unwrap(), [1; 4]);
+    }
+
+    #[test]
+    fn test_equatable_and_clone() {
+        let x: Equatable<Pinned<[u8; 32]>> = Equatable::new([7; 32]);
+        let y = Equatable(x.0.clone());
+        assert_eq!(x, y);
+    }
+
+    #[test]
+    fn test_opaque_debug() {
+        let x: Pinned<[u8; 4]> = Pinned::new([1; 4]);
+        assert_eq!(format!("{x:?}"), "Pinned<[u8; 4]> { ... }");
+    }
+}
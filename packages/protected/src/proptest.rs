@@ -0,0 +1,125 @@
+//! [proptest] strategies for controlled types.
+//!
+//! Every adapter stack that implements [Controlled] implements [Arbitrary] when its inner value does,
+//! so `any::<Equatable<Exportable<Protected<[u8; 32]>>>>()` just works.
+//! Use [controlled] to build a stack from a custom strategy for the inner value.
+//!
+//! # Example
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use vitaminc_protected::{proptest::controlled, Controlled, Equatable, Protected};
+//!
+//! proptest!(|(x in controlled::<Equatable<Protected<String>>, _>("[a-z]{1,8}"))| {
+//!     prop_assert!(x.expose(|s| s.len() <= 8));
+//! });
+//! ```
+use crate::{
+    private::ControlledPrivate, Controlled, Equatable, Exportable, Protected, Scope, Usage,
+};
+use core::fmt::Debug;
+use proptest::{
+    arbitrary::{any_with, Arbitrary},
+    strategy::{Map, Strategy},
+};
+use zeroize::Zeroize;
+
+/// Generate a [Controlled] type from a strategy for its inner value.
+pub fn controlled<C, S>(inner: S) -> Map<S, fn(C::Inner) -> C>
+where
+    C: Controlled + Debug,
+    S: Strategy<Value = C::Inner>,
+{
+    inner.prop_map(C::new)
+}
+
+impl<T> Arbitrary for Protected<T>
+where
+    T: Arbitrary + Zeroize,
+{
+    type Parameters = T::Parameters;
+    type Strategy = Map<T::Strategy, fn(T) -> Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        any_with::<T>(args).prop_map(Protected::new)
+    }
+}
+
+macro_rules! impl_arbitrary {
+    ($($type:ident),+) => {
+        $(
+            impl<T> Arbitrary for $type<T>
+            where
+                Self: Controlled + Debug,
+                <Self as ControlledPrivate>::Inner: Arbitrary,
+            {
+                type Parameters = <<Self as ControlledPrivate>::Inner as Arbitrary>::Parameters;
+                type Strategy = Map<
+                    <<Self as ControlledPrivate>::Inner as Arbitrary>::Strategy,
+                    fn(<Self as ControlledPrivate>::Inner) -> Self,
+                >;
+
+                fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+                    controlled(any_with::<<Self as ControlledPrivate>::Inner>(args))
+                }
+            }
+        )+
+    };
+}
+
+impl_arbitrary!(Equatable, Exportable);
+
+impl<T, S> Arbitrary for Usage<T, S>
+where
+    Self: Controlled + Debug,
+    <Self as ControlledPrivate>::Inner: Arbitrary,
+    S: Scope,
+{
+    type Parameters = <<Self as ControlledPrivate>::Inner as Arbitrary>::Parameters;
+    type Strategy = Map<
+        <<Self as ControlledPrivate>::Inner as Arbitrary>::Strategy,
+        fn(<Self as ControlledPrivate>::Inner) -> Self,
+    >;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        controlled(any_with::<<Self as ControlledPrivate>::Inner>(args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn test_protected(x in any::<Protected<[u8; 16]>>()) {
+            prop_assert_eq!(x.risky_unwrap().len(), 16);
+        }
+
+        #[test]
+        fn test_adapter_stacks(
+            x in any::<Equatable<Exportable<Protected<u64>>>>(),
+            y in any::<Exportable<Equatable<Protected<[u8; 8]>>>>(),
+        ) {
+            let inner = x.expose(|x| *x);
+            prop_assert_eq!(x, Equatable::<Exportable<Protected<u64>>>::new(inner));
+            let inner = y.expose(|y| *y);
+            prop_assert_eq!(y, Exportable::<Equatable<Protected<[u8; 8]>>>::new(inner));
+        }
+
+        #[test]
+        fn test_controlled(x in controlled::<Exportable<Protected<u8>>, _>(1..10u8)) {
+            prop_assert!(x.expose(|x| (1..10).contains(x)));
+        }
+
+        #[test]
+        fn test_usage(
+            x in controlled::<Usage<Protected<u8>>, _>(1..10u8),
+            y in any::<Usage<Equatable<Protected<[u8; 8]>>>>(),
+        ) {
+            prop_assert!(x.expose(|x| (1..10).contains(x)));
+            prop_assert_eq!(y.expose(|y| y.len()), 8);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use super::Controlled;
 use crate::private::ControlledPrivate;
+use core::ops::Range;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// The most basic controlled type.
@@ -54,6 +55,19 @@ impl<T> Protected<Option<T>> {
     }
 }
 
+impl Protected<Range<usize>> {
+    /// Create a [Protected] range from [Protected] bounds so that it can be used to index slices.
+    ///
+    /// ```
+    /// use vitaminc_protected::{Controlled, Protected};
+    /// let range = Protected::range(Protected::new(1), Protected::new(3));
+    /// assert_eq!([1, 2, 3, 4][range], [2, 3]);
+    /// ```
+    pub fn range(start: Protected<usize>, end: Protected<usize>) -> Self {
+        Self(start.0..end.0)
+    }
+}
+
 impl<T: Zeroize> ZeroizeOnDrop for Protected<T> {}
 
 impl<T> ControlledPrivate for Protected<T>
@@ -73,17 +87,14 @@ where
     fn inner_mut(&mut self) -> &mut Self::Inner {
         &mut self.0
     }
-}
 
-impl<T> Controlled for Protected<T>
-where
-    T: Zeroize,
-{
-    fn risky_unwrap(self) -> Self::Inner {
+    fn into_inner(self) -> Self::Inner {
         self.0
     }
 }
 
+impl<T> Controlled for Protected<T> where T: Zeroize {}
+
 impl<T> Copy for Protected<T> where T: Copy {}
 
 impl<T> Clone for Protected<T>
@@ -115,7 +126,7 @@ where
 {
     let mut out: [T; N] = [Default::default(); N];
     array.iter().enumerate().for_each(|(i, x)| {
-        out[i] = x.risky_unwrap();
+        out[i] = x.into_inner();
     });
     Protected::new(out)
 }
@@ -123,6 +134,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::format;
 
     #[test]
     fn test_new_array() {
@@ -0,0 +1,355 @@
+//! Redacted serialization for logs and diagnostics.
+//!
+//! Controlled values are normally either impossible to serialize ([Protected]) or serialize
+//! their inner value ([crate::Exportable]). Neither is what you want when a whole domain struct
+//! is written to a structured log.
+//!
+//! [redacted] wraps a reference to any controlled value (including [crate::Exportable], [crate::Equatable]
+//! and [crate::Usage]) in a [Redacted] value that serializes as a placeholder instead of its inner value.
+//! [serialize_redacted] and [serialize_redacted_len] do the same for fields of structs that derive `Serialize`
+//! (with `#[serde(serialize_with = "...")]`).
+//! [Protected] itself never implements `Serialize` so forgetting to redact a field is a compile error.
+//!
+//! [Redaction::Placeholder] never reads the inner value, so redacting adapters such as [crate::Ephemeral]
+//! or `Limited` doesn't use them up. The other modes write [REDACTED] if the inner value can't be
+//! accessed any more (e.g. a used [crate::Ephemeral]).
+//!
+//! # Example
+//!
+//! ```
+//! use vitaminc_protected::{redacted, serialize_redacted_len, Exportable, Protected, Redaction};
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Request {
+//!     user: String,
+//!     #[serde(serialize_with = "serialize_redacted_len")]
+//!     token: Protected<[u8; 32]>,
+//!     #[serde(serialize_with = "serialize_redacted_len")]
+//!     key: Exportable<Protected<[u8; 16]>>,
+//! }
+//!
+//! let request = Request {
+//!     user: "alice".to_string(),
+//!     token: Protected::new([1; 32]),
+//!     key: Exportable::new([2; 16]),
+//! };
+//!
+//! let log = serde_json::to_string(&request).unwrap();
+//! assert_eq!(log, r#"{"user":"alice","token":"[REDACTED len=32]","key":"[REDACTED len=16]"}"#);
+//!
+//! // Individual values can be redacted with a keyed fingerprint
+//! # #[cfg(feature = "fingerprint")] {
+//! let fingerprint_key = Protected::new([7; 32]);
+//! let token = redacted(&request.token, Redaction::Fingerprint(&fingerprint_key));
+//! assert!(serde_json::to_string(&token).unwrap().starts_with(r#""[REDACTED fingerprint="#));
+//! # }
+//! ```
+//!
+//! Serializing a [Protected] value without redacting it doesn't compile:
+//!
+//! ```compile_fail
+//! use vitaminc_protected::Protected;
+//!
+//! serde_json::to_string(&Protected::new([0u8; 32])).unwrap();
+//! ```
+use crate::{exportable::SafeSerialize, Controlled, Protected};
+use serde::{Serialize, Serializer};
+
+/// The placeholder written in place of redacted values.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// How a [Redacted] value is written when serialized.
+#[derive(Clone, Copy, Debug)]
+pub enum Redaction<'k> {
+    /// Write [REDACTED].
+    Placeholder,
+    /// Write [REDACTED] along with the length in bytes of the inner value.
+    Length,
+    /// Write [REDACTED] along with a [Fingerprint] of the inner value keyed with the given key,
+    /// so that log readers can tell whether two values are the same without being able to guess them.
+    /// Values that aren't bytes, strings or integers are written as [REDACTED],
+    /// as is every value when the `fingerprint` feature (enabled by default) is disabled.
+    Fingerprint(&'k Protected<[u8; 32]>),
+}
+
+/// A reference to a controlled value that serializes according to a [Redaction] mode.
+/// Returned by [redacted].
+pub struct Redacted<'a, T: ?Sized> {
+    value: &'a T,
+    mode: Redaction<'a>,
+}
+
+/// Wrap `value` so that it serializes as a placeholder instead of its inner value.
+pub fn redacted<'a, T>(value: &'a T, mode: Redaction<'a>) -> Redacted<'a, T>
+where
+    T: Controlled,
+    T::Inner: SafeSerialize,
+{
+    Redacted { value, mode }
+}
+
+impl<T> Serialize for Redacted<'_, T>
+where
+    T: Controlled,
+    T::Inner: SafeSerialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.mode {
+            Redaction::Placeholder => serializer.serialize_str(REDACTED),
+            Redaction::Length => serialize_len(self.value, serializer),
+            Redaction::Fingerprint(key) => match self
+                .value
+                .try_with_inner(|x| fingerprint(x, key))
+                .ok()
+                .flatten()
+            {
+                Some(fingerprint) => {
+                    serializer.collect_str(&format_args!("[REDACTED fingerprint={fingerprint}]"))
+                }
+                None => serializer.serialize_str(REDACTED),
+            },
+        }
+    }
+}
+
+/// Serialize a controlled value as [REDACTED].
+/// For use with `#[serde(serialize_with = "serialize_redacted")]`.
+///
+/// The inner value is never read so this works for any controlled type.
+pub fn serialize_redacted<T, S>(_: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Controlled,
+    S: Serializer,
+{
+    serializer.serialize_str(REDACTED)
+}
+
+/// Serialize a controlled value as [REDACTED] along with its length.
+/// For use with `#[serde(serialize_with = "serialize_redacted_len")]`.
+pub fn serialize_redacted_len<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Controlled,
+    T::Inner: RedactedLen,
+    S: Serializer,
+{
+    serialize_len(value, serializer)
+}
+
+/// Values with a length in bytes, as reported in [Redaction::Length] output.
+///
+/// Implemented for every [SafeSerialize] type as well as byte slices and vectors.
+pub trait RedactedLen {
+    /// The length in bytes of the value.
+    fn redacted_len(&self) -> usize;
+}
+
+impl<T: SafeSerialize + ?Sized> RedactedLen for T {
+    fn redacted_len(&self) -> usize {
+        SafeSerialize::redacted_len(self)
+    }
+}
+
+impl RedactedLen for [u8] {
+    fn redacted_len(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl RedactedLen for alloc::vec::Vec<u8> {
+    fn redacted_len(&self) -> usize {
+        self.len()
+    }
+}
+
+fn serialize_len<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Controlled,
+    T::Inner: RedactedLen,
+    S: Serializer,
+{
+    // Values that can't be accessed any more (e.g. a used `Ephemeral`) are written as a placeholder
+    match value.try_with_inner(|x| x.redacted_len()) {
+        Ok(len) => serializer.collect_str(&format_args!("[REDACTED len={len}]")),
+        Err(_) => serializer.serialize_str(REDACTED),
+    }
+}
+
+/// Fingerprint `value` with `key` if it holds bytes, a string or an integer.
+#[cfg(feature = "fingerprint")]
+fn fingerprint<T: SafeSerialize + ?Sized>(
+    value: &T,
+    key: &Protected<[u8; 32]>,
+) -> Option<crate::Fingerprint> {
+    value.with_redacted_bytes(|bytes| {
+        bytes.map(|bytes| crate::Fingerprint::compute(Some(&key.0), bytes))
+    })
+}
+
+#[cfg(not(feature = "fingerprint"))]
+fn fingerprint<T: SafeSerialize + ?Sized>(
+    _: &T,
+    _: &Protected<[u8; 32]>,
+) -> Option<core::convert::Infallible> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Ephemeral, Equatable, Exportable, Usage};
+    use std::{
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    #[derive(Serialize)]
+    struct Request {
+        id: u32,
+        #[serde(serialize_with = "serialize_redacted")]
+        token: Protected<[u8; 32]>,
+        #[serde(serialize_with = "serialize_redacted_len")]
+        password: Equatable<Exportable<Protected<String>>>,
+        #[serde(serialize_with = "serialize_redacted_len")]
+        tag: Equatable<Protected<[u8; 16]>>,
+        #[serde(serialize_with = "serialize_redacted")]
+        key: Usage<Exportable<Protected<[u8; 8]>>>,
+    }
+
+    #[test]
+    fn test_struct_fields() {
+        let request = Request {
+            id: 7,
+            token: Protected::new([1; 32]),
+            password: Equatable::new("hunter2".to_string()),
+            tag: Equatable::new([2; 16]),
+            key: Usage::new([3; 8]),
+        };
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"id":7,"token":"[REDACTED]","password":"[REDACTED len=7]","tag":"[REDACTED len=16]","key":"[REDACTED]"}"#
+        );
+    }
+
+    #[test]
+    fn test_placeholder_and_length() {
+        let x = Protected::new([1u8; 4]);
+        let json = |mode| serde_json::to_string(&redacted(&x, mode)).unwrap();
+        assert_eq!(json(Redaction::Placeholder), r#""[REDACTED]""#);
+        assert_eq!(json(Redaction::Length), r#""[REDACTED len=4]""#);
+    }
+
+    #[cfg(feature = "fingerprint")]
+    #[test]
+    fn test_fingerprint() {
+        use crate::AsFingerprint;
+        use std::format;
+
+        let key = Protected::new([9u8; 32]);
+        let x: Equatable<Protected<String>> = Equatable::new("hunter2".to_string());
+        assert_eq!(
+            serde_json::to_string(&redacted(&x, Redaction::Fingerprint(&key))).unwrap(),
+            format!(
+                r#""[REDACTED fingerprint={}]""#,
+                x.fingerprint_with_key(&key)
+            )
+        );
+
+        let n: Protected<u16> = Protected::new(0x0102);
+        assert_eq!(
+            serde_json::to_string(&redacted(&n, Redaction::Fingerprint(&key))).unwrap(),
+            format!(
+                r#""[REDACTED fingerprint={}]""#,
+                Protected::new([1u8, 2]).fingerprint_with_key(&key)
+            )
+        );
+
+        let b = Protected::new(true);
+        assert_eq!(
+            serde_json::to_string(&redacted(&b, Redaction::Fingerprint(&key))).unwrap(),
+            r#""[REDACTED]""#
+        );
+    }
+
+    #[test]
+    fn test_ephemeral() {
+        let x: Ephemeral<Protected<[u8; 4]>> = Ephemeral::new([1; 4]);
+        let json = |mode| serde_json::to_string(&redacted(&x, mode)).unwrap();
+
+        // Placeholders don't use the secret up
+        assert_eq!(json(Redaction::Placeholder), r#""[REDACTED]""#);
+        assert!(!x.is_used());
+
+        assert_eq!(json(Redaction::Length), r#""[REDACTED len=4]""#);
+        assert!(x.is_used());
+        assert_eq!(json(Redaction::Length), r#""[REDACTED]""#);
+        assert_eq!(json(Redaction::Placeholder), r#""[REDACTED]""#);
+        assert_eq!(
+            json(Redaction::Fingerprint(&Protected::new([9; 32]))),
+            r#""[REDACTED]""#
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_limited() {
+        use crate::Limited;
+
+        #[derive(Serialize)]
+        struct Session {
+            #[serde(serialize_with = "serialize_redacted")]
+            key: Limited<Protected<[u8; 16]>, 1>,
+            #[serde(serialize_with = "serialize_redacted_len")]
+            mac: Limited<Protected<[u8; 8]>, 1>,
+        }
+
+        let session = Session {
+            key: Limited::new([0; 16]),
+            mac: Limited::new([0; 8]),
+        };
+        let expected = r#"{"key":"[REDACTED]","mac":"[REDACTED len=8]"}"#;
+        assert_eq!(serde_json::to_string(&session).unwrap(), expected);
+        assert_eq!(session.key.remaining(), 1);
+        assert!(session.mac.is_exhausted());
+
+        assert_eq!(
+            serde_json::to_string(&session).unwrap(),
+            r#"{"key":"[REDACTED]","mac":"[REDACTED]"}"#
+        );
+    }
+
+    #[test]
+    fn test_byte_vectors() {
+        #[derive(Serialize)]
+        struct Upload {
+            #[serde(serialize_with = "serialize_redacted")]
+            contents: Protected<Vec<u8>>,
+            #[serde(serialize_with = "serialize_redacted_len")]
+            key: Protected<Vec<u8>>,
+        }
+
+        let upload = Upload {
+            contents: Protected::new(vec![1, 2, 3]),
+            key: Protected::new(vec![0; 32]),
+        };
+        assert_eq!(
+            serde_json::to_string(&upload).unwrap(),
+            r#"{"contents":"[REDACTED]","key":"[REDACTED len=32]"}"#
+        );
+    }
+
+    #[test]
+    fn test_exportable_not_redacted_by_default() {
+        let x: Exportable<Protected<u8>> = Exportable::new(42);
+        assert_eq!(serde_json::to_string(&x).unwrap(), "42");
+        assert_eq!(
+            serde_json::to_string(&redacted(&x, Redaction::Placeholder)).unwrap(),
+            r#""[REDACTED]""#
+        );
+    }
+}
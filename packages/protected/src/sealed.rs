@@ -0,0 +1,286 @@
+use crate::{private::ControlledPrivate, Controlled, Envelope, EnvelopeError, TypeIdentifier};
+use chacha20poly1305::{
+    aead::{AeadCore, AeadInPlace, KeyInit, OsRng},
+    Key, Tag, XChaCha20Poly1305, XNonce,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::marker::PhantomData;
+use thiserror::Error;
+use zeroize::Zeroize;
+
+const SEALED_VERSION: u8 = 1;
+const NONCE_LEN: usize = 24;
+const TAG_LEN: usize = 16;
+const AAD_PREFIX: &[u8] = b"vitaminc.sealed.v1:";
+
+#[derive(Error, Debug)]
+pub enum SealError {
+    #[error("Encryption failed")]
+    EncryptionFailed,
+    #[error("Decryption failed (wrong key, type or corrupted ciphertext)")]
+    DecryptionFailed,
+    #[error("Unsupported sealed version {0}")]
+    UnsupportedVersion(u8),
+    #[error("Sealed value is truncated")]
+    Truncated,
+    #[error(transparent)]
+    Envelope(#[from] EnvelopeError),
+}
+
+/// An encrypted form of a [Controlled] value that is safe to persist (for example, in a database).
+///
+/// Values are sealed with XChaCha20-Poly1305 under a 32-byte key-encryption key using a random nonce.
+/// The plaintext is the [Envelope] encoding of the value and the [TypeIdentifier] is bound
+/// to the ciphertext as associated data so a sealed value can only be unsealed into the
+/// same type and scope that it was sealed from.
+///
+/// Because the ciphertext does not reveal the secret, [Sealed] implements `Serialize` and `Deserialize`.
+///
+/// # Example
+///
+/// ```
+/// use vitaminc_protected::{Controlled, Exportable, Protected, Scope, Sealed, TypeIdentifier, Usage};
+///
+/// struct DataKey;
+/// impl Scope for DataKey {}
+/// impl TypeIdentifier for DataKey {
+///     const TYPE_ID: &'static str = "example.data-key";
+/// }
+///
+/// let kek = Protected::new([1u8; 32]);
+/// let key: Usage<Exportable<Protected<[u8; 32]>>, DataKey> = Usage::new([42; 32]);
+///
+/// let sealed = Sealed::seal(&key, &kek).unwrap();
+/// let stored = serde_json::to_string(&sealed).unwrap();
+///
+/// let loaded: Sealed<Usage<Exportable<Protected<[u8; 32]>>, DataKey>> = serde_json::from_str(&stored).unwrap();
+/// assert_eq!(loaded.unseal(&kek).unwrap().risky_unwrap(), [42; 32]);
+/// ```
+pub struct Sealed<T> {
+    ciphertext: Vec<u8>,
+    _type: PhantomData<T>,
+}
+
+impl<T> Sealed<T>
+where
+    T: Envelope + TypeIdentifier,
+{
+    /// Encrypt `value` under the key-encryption key `kek`.
+    pub fn seal<K>(value: &T, kek: &K) -> Result<Self, SealError>
+    where
+        K: Controlled<Inner = [u8; 32]>,
+    {
+        let cipher = kek.with_inner(|kek| XChaCha20Poly1305::new(Key::from_slice(kek)));
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+        // The envelope is encrypted in place so plaintext is never copied
+        let mut buffer = value.to_envelope()?.into_inner();
+        let tag = cipher
+            .encrypt_in_place_detached(&nonce, &associated_data::<T>(), &mut buffer)
+            .map_err(|_| {
+                buffer.zeroize();
+                SealError::EncryptionFailed
+            })?;
+
+        let mut ciphertext = Vec::with_capacity(1 + NONCE_LEN + buffer.len() + TAG_LEN);
+        ciphertext.push(SEALED_VERSION);
+        ciphertext.extend_from_slice(&nonce);
+        ciphertext.extend_from_slice(&buffer);
+        ciphertext.extend_from_slice(&tag);
+
+        Ok(Self {
+            ciphertext,
+            _type: PhantomData,
+        })
+    }
+
+    /// Decrypt and decode the sealed value.
+    /// Plaintext intermediates are zeroized whether or not unsealing succeeds.
+    pub fn unseal<K>(&self, kek: &K) -> Result<T, SealError>
+    where
+        K: Controlled<Inner = [u8; 32]>,
+    {
+        let (version, rest) = self.ciphertext.split_first().ok_or(SealError::Truncated)?;
+        if *version != SEALED_VERSION {
+            return Err(SealError::UnsupportedVersion(*version));
+        }
+        if rest.len() < NONCE_LEN + TAG_LEN {
+            return Err(SealError::Truncated);
+        }
+        let (nonce, rest) = rest.split_at(NONCE_LEN);
+        let (body, tag) = rest.split_at(rest.len() - TAG_LEN);
+
+        let cipher = kek.with_inner(|kek| XChaCha20Poly1305::new(Key::from_slice(kek)));
+        let mut buffer = body.to_vec();
+        let result = cipher
+            .decrypt_in_place_detached(
+                XNonce::from_slice(nonce),
+                &associated_data::<T>(),
+                &mut buffer,
+                Tag::from_slice(tag),
+            )
+            .map_err(|_| SealError::DecryptionFailed)
+            .and_then(|_| T::from_envelope(&buffer).map_err(SealError::from));
+
+        buffer.zeroize();
+        result
+    }
+}
+
+impl<T> Sealed<T> {
+    /// The raw sealed bytes (version, nonce, ciphertext and tag).
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.ciphertext
+    }
+
+    /// Load sealed bytes previously returned by [Sealed::as_bytes].
+    /// The bytes are only authenticated when they are unsealed.
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Self {
+        Self {
+            ciphertext: bytes.into(),
+            _type: PhantomData,
+        }
+    }
+}
+
+fn associated_data<T: TypeIdentifier>() -> Vec<u8> {
+    [AAD_PREFIX, T::TYPE_ID.as_bytes()].concat()
+}
+
+impl<T> Clone for Sealed<T> {
+    fn clone(&self) -> Self {
+        Self::from_bytes(self.ciphertext.clone())
+    }
+}
+
+impl<T> std::fmt::Debug for Sealed<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sealed")
+            .field("len", &self.ciphertext.len())
+            .finish()
+    }
+}
+
+impl<T> Serialize for Sealed<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.ciphertext)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Sealed<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        serde_bytes::ByteBuf::deserialize(deserializer).map(|buf| Self::from_bytes(buf.into_vec()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Equatable, Exportable, Protected, Scope, Usage};
+
+    struct DataKey;
+    impl Scope for DataKey {}
+    impl TypeIdentifier for DataKey {
+        const TYPE_ID: &'static str = "test.data-key";
+    }
+
+    struct OtherKey;
+    impl Scope for OtherKey {}
+    impl TypeIdentifier for OtherKey {
+        const TYPE_ID: &'static str = "test.other-key";
+    }
+
+    type Key = Usage<Exportable<Protected<[u8; 32]>>, DataKey>;
+    type Other = Usage<Exportable<Protected<[u8; 32]>>, OtherKey>;
+
+    fn kek(byte: u8) -> Protected<[u8; 32]> {
+        Protected::new([byte; 32])
+    }
+
+    #[test]
+    fn test_seal_unseal() {
+        let sealed = Sealed::seal(&Key::new([7; 32]), &kek(1)).unwrap();
+        assert_eq!(sealed.as_bytes().len(), 1 + NONCE_LEN + 55 + TAG_LEN);
+        assert_eq!(sealed.unseal(&kek(1)).unwrap().risky_unwrap(), [7; 32]);
+    }
+
+    #[test]
+    fn test_unseal_nested_stack() {
+        type Password = Usage<Equatable<Exportable<Protected<String>>>, DataKey>;
+        let sealed = Sealed::seal(&Password::new("hunter2".to_string()), &kek(1)).unwrap();
+        assert_eq!(sealed.unseal(&kek(1)).unwrap().risky_unwrap(), "hunter2");
+    }
+
+    #[test]
+    fn test_masked_kek() {
+        let kek = crate::Masked::new([1; 32]);
+        let sealed = Sealed::seal(&Key::new([7; 32]), &kek).unwrap();
+        assert_eq!(sealed.unseal(&kek).unwrap().risky_unwrap(), [7; 32]);
+    }
+
+    #[test]
+    fn test_random_nonce() {
+        let a = Sealed::seal(&Key::new([7; 32]), &kek(1)).unwrap();
+        let b = Sealed::seal(&Key::new([7; 32]), &kek(1)).unwrap();
+        assert_ne!(a.as_bytes(), b.as_bytes());
+    }
+
+    #[test]
+    fn test_wrong_key() {
+        let sealed = Sealed::seal(&Key::new([7; 32]), &kek(1)).unwrap();
+        assert!(matches!(
+            sealed.unseal(&kek(2)),
+            Err(SealError::DecryptionFailed)
+        ));
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        let sealed = Sealed::seal(&Key::new([7; 32]), &kek(1)).unwrap();
+        let other: Sealed<Other> = Sealed::from_bytes(sealed.as_bytes());
+        assert!(matches!(
+            other.unseal(&kek(1)),
+            Err(SealError::DecryptionFailed)
+        ));
+    }
+
+    #[test]
+    fn test_tampered() {
+        let sealed = Sealed::seal(&Key::new([7; 32]), &kek(1)).unwrap();
+        let mut bytes = sealed.as_bytes().to_vec();
+        bytes[30] ^= 1;
+        assert!(matches!(
+            Sealed::<Key>::from_bytes(bytes).unseal(&kek(1)),
+            Err(SealError::DecryptionFailed)
+        ));
+        assert!(matches!(
+            Sealed::<Key>::from_bytes(vec![SEALED_VERSION; 10]).unseal(&kek(1)),
+            Err(SealError::Truncated)
+        ));
+        assert!(matches!(
+            Sealed::<Key>::from_bytes(vec![9; 64]).unseal(&kek(1)),
+            Err(SealError::UnsupportedVersion(9))
+        ));
+    }
+
+    #[test]
+    fn test_serialize_deserialize() {
+        let sealed = Sealed::seal(&Key::new([7; 32]), &kek(1)).unwrap();
+        let bytes = bincode::serialize(&sealed).unwrap();
+        let loaded: Sealed<Key> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(loaded.as_bytes(), sealed.as_bytes());
+        assert_eq!(loaded.unseal(&kek(1)).unwrap().risky_unwrap(), [7; 32]);
+    }
+
+    #[test]
+    fn test_debug() {
+        let sealed = Sealed::seal(&Key::new([7; 32]), &kek(1)).unwrap();
+        assert_eq!(format!("{sealed:?}"), "Sealed { len: 96 }");
+    }
+}
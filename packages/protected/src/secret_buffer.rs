@@ -0,0 +1,250 @@
+use std::{io, ptr::NonNull};
+use zeroize::Zeroize;
+
+/// Where the memory of a [SecretBuffer] comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SecretBufferBackend {
+    /// Pages created with `memfd_secret` (Linux 5.14+) which are removed from the kernel direct map
+    /// so they can't be read by the kernel or other processes (even with `ptrace` or `/proc/pid/mem`).
+    MemfdSecret,
+    /// Anonymous pages locked with `mlock` (so they are never swapped) and excluded from core dumps.
+    /// Used when `memfd_secret` is not available (e.g. older kernels or `secretmem.enable=0`).
+    Mlock,
+}
+
+/// A fixed size byte buffer backed by secret memory (Linux only).
+///
+/// Memory is allocated with `memfd_secret` when the kernel supports it and falls back to `mlock`ed,
+/// non-dumpable anonymous pages otherwise. [SecretBuffer::backend] reports which one was used.
+/// Allocations are rounded up to whole pages and count towards `RLIMIT_MEMLOCK`
+/// so they are best suited to keys and other small secrets.
+///
+/// The buffer is zeroized before the memory is unmapped.
+/// Use it as the inner value of a [crate::Protected] to keep the usual controlled API.
+///
+/// # Example
+///
+/// ```
+/// use vitaminc_protected::{AsProtectedRef, Controlled, Protected, ProtectedRef, SecretBuffer};
+///
+/// let mut key: Protected<SecretBuffer> = Protected::generate_ok(|| SecretBuffer::new(32))?;
+/// key.update(|k| k.as_mut().fill(42));
+///
+/// let key_ref: ProtectedRef<[u8]> = key.as_protected_ref();
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct SecretBuffer {
+    ptr: NonNull<u8>,
+    len: usize,
+    mapped_len: usize,
+    backend: SecretBufferBackend,
+}
+
+// The buffer exclusively owns its mapping
+unsafe impl Send for SecretBuffer {}
+unsafe impl Sync for SecretBuffer {}
+
+impl SecretBuffer {
+    /// Allocate a zeroed buffer of `len` bytes, preferring `memfd_secret`.
+    pub fn new(len: usize) -> io::Result<Self> {
+        let mapped_len = round_to_pages(len)?;
+        match map_memfd_secret(mapped_len) {
+            Ok(ptr) => Ok(Self {
+                ptr,
+                len,
+                mapped_len,
+                backend: SecretBufferBackend::MemfdSecret,
+            }),
+            Err(_) => Self::new_mlocked(len),
+        }
+    }
+
+    /// Allocate a zeroed buffer of `len` bytes with `mlock` (without trying `memfd_secret`).
+    pub fn new_mlocked(len: usize) -> io::Result<Self> {
+        let mapped_len = round_to_pages(len)?;
+        Ok(Self {
+            ptr: map_mlocked(mapped_len)?,
+            len,
+            mapped_len,
+            backend: SecretBufferBackend::Mlock,
+        })
+    }
+
+    /// Returns `true` if the running kernel supports `memfd_secret`.
+    pub fn is_memfd_secret_supported() -> bool {
+        match memfd_secret() {
+            Ok(fd) => {
+                unsafe { libc::close(fd) };
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn backend(&self) -> SecretBufferBackend {
+        self.backend
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl AsRef<[u8]> for SecretBuffer {
+    fn as_ref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl AsMut<[u8]> for SecretBuffer {
+    fn as_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Zeroize for SecretBuffer {
+    fn zeroize(&mut self) {
+        self.as_mut().zeroize();
+    }
+}
+
+impl Drop for SecretBuffer {
+    fn drop(&mut self) {
+        self.zeroize();
+        let addr = self.ptr.as_ptr().cast();
+        unsafe {
+            if self.backend == SecretBufferBackend::Mlock {
+                libc::munlock(addr, self.mapped_len);
+            }
+            libc::munmap(addr, self.mapped_len);
+        }
+    }
+}
+
+opaque_debug::implement!(SecretBuffer);
+
+/// At least one page is always mapped so that empty buffers still have a valid pointer.
+fn round_to_pages(len: usize) -> io::Result<usize> {
+    let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    len.max(1)
+        .checked_next_multiple_of(page)
+        .ok_or_else(|| io::Error::from(io::ErrorKind::OutOfMemory))
+}
+
+fn memfd_secret() -> io::Result<libc::c_int> {
+    let fd = unsafe { libc::syscall(libc::SYS_memfd_secret, libc::O_CLOEXEC) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(fd as libc::c_int)
+}
+
+fn map_memfd_secret(len: usize) -> io::Result<NonNull<u8>> {
+    let fd = memfd_secret()?;
+    let result = (|| {
+        if unsafe { libc::ftruncate(fd, len as libc::off_t) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // The mapping keeps the memory alive after the descriptor is closed
+        mmap(len, libc::MAP_SHARED, fd)
+    })();
+    unsafe { libc::close(fd) };
+    result
+}
+
+fn map_mlocked(len: usize) -> io::Result<NonNull<u8>> {
+    let ptr = mmap(len, libc::MAP_PRIVATE | libc::MAP_ANONYMOUS, -1)?;
+    let addr = ptr.as_ptr().cast();
+    unsafe {
+        if libc::mlock(addr, len) != 0 || libc::madvise(addr, len, libc::MADV_DONTDUMP) != 0 {
+            let err = io::Error::last_os_error();
+            // Unmapping also unlocks the pages
+            libc::munmap(addr, len);
+            return Err(err);
+        }
+    }
+    Ok(ptr)
+}
+
+fn mmap(len: usize, flags: libc::c_int, fd: libc::c_int) -> io::Result<NonNull<u8>> {
+    let ptr = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            flags,
+            fd,
+            0,
+        )
+    };
+    if ptr == libc::MAP_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+    NonNull::new(ptr.cast()).ok_or_else(|| io::Error::from(io::ErrorKind::OutOfMemory))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AsProtectedRef, Controlled, Equatable, Protected};
+
+    #[test]
+    fn test_backend_matches_kernel_support() {
+        let buffer = SecretBuffer::new(32).unwrap();
+        let expected = if SecretBuffer::is_memfd_secret_supported() {
+            SecretBufferBackend::MemfdSecret
+        } else {
+            SecretBufferBackend::Mlock
+        };
+        assert_eq!(buffer.backend(), expected);
+        assert_eq!(buffer.as_ref(), [0; 32]);
+    }
+
+    #[test]
+    fn test_memfd_secret() {
+        // Covered by `test_mlocked` on kernels without memfd_secret
+        if !SecretBuffer::is_memfd_secret_supported() {
+            return;
+        }
+        let mut buffer = SecretBuffer::new(4096 + 1).unwrap();
+        buffer.as_mut().fill(7);
+        assert!(buffer.as_ref().iter().all(|b| *b == 7));
+    }
+
+    #[test]
+    fn test_mlocked() {
+        let mut buffer = SecretBuffer::new_mlocked(16).unwrap();
+        assert_eq!(buffer.backend(), SecretBufferBackend::Mlock);
+        buffer.as_mut().copy_from_slice(&[1; 16]);
+        buffer.zeroize();
+        assert_eq!(buffer.as_ref(), [0; 16]);
+    }
+
+    #[test]
+    fn test_empty() {
+        let buffer = SecretBuffer::new(0).unwrap();
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.as_ref(), &[] as &[u8]);
+    }
+
+    #[test]
+    fn test_protected() {
+        let mut x: Protected<SecretBuffer> =
+            Protected::generate_ok(|| SecretBuffer::new(4)).unwrap();
+        x.update(|b| b.as_mut().copy_from_slice(&[1, 2, 3, 4]));
+        let y: Equatable<Protected<[u8; 4]>> = Equatable::new([1, 2, 3, 4]);
+        let mut copy = Protected::new([0u8; 4]);
+        copy.update_with_ref(x.as_protected_ref(), |c, b: &[u8]| c.copy_from_slice(b));
+        assert_eq!(y, Equatable::<Protected<[u8; 4]>>::from(copy));
+    }
+
+    #[test]
+    fn test_opaque_debug() {
+        let x = SecretBuffer::new(4).unwrap();
+        assert_eq!(format!("{x:?}"), "SecretBuffer { ... }");
+    }
+}
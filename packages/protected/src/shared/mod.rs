@@ -0,0 +1,247 @@
+use crate::{AsProtectedRef, Controlled, ProtectedRef};
+use std::{
+    fmt::Debug,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+use zeroize::Zeroize;
+
+/// A thread-safe, reference counted handle to a [Controlled] value.
+///
+/// Cloning a [SharedProtected] is cheap and only clones the handle.
+/// Readers access the value through a [ProtectedRef] (or a reference to the controlled value) while holding a read lock,
+/// and [SharedProtected::rotate] or [SharedProtected::write] take a write lock so a key can be replaced
+/// without readers ever observing a partially written value.
+///
+/// Unlike `Arc<Protected<T>>`, the inner value is zeroized as soon as the last handle is dropped,
+/// and any value replaced by [SharedProtected::rotate] is zeroized before the write lock is released.
+///
+/// `SharedProtected<T>` is `Send` and `Sync` whenever `T` is `Send + Sync`
+/// (which is the case for [crate::Protected] of any `Send + Sync` type) so handles can be moved into threads or async tasks.
+///
+/// # Example
+///
+/// ```
+/// use vitaminc_protected::{Controlled, Protected, ProtectedRef, SharedProtected};
+///
+/// let key: SharedProtected<Protected<[u8; 32]>> = SharedProtected::new([1; 32]);
+///
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         let key = key.clone();
+///         std::thread::spawn(move || {
+///             let mut local = Protected::new([0; 32]);
+///             key.with_protected_ref(|k: ProtectedRef<[u8]>| {
+///                 local.update_with_ref(k, |local, k| local.copy_from_slice(k));
+///             });
+///             local
+///         })
+///     })
+///     .collect();
+///
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap().risky_unwrap(), [1; 32]);
+/// }
+///
+/// // Rotate the key for all handles
+/// key.rotate(Protected::new([2; 32]));
+/// assert_eq!(key.read(|k| k.risky_unwrap()), [2; 32]);
+/// ```
+pub struct SharedProtected<T: Zeroize>(Arc<Shared<T>>);
+
+/// The shared allocation. Zeroizes the value when the last handle is dropped.
+struct Shared<T: Zeroize>(RwLock<T>);
+
+impl<T: Zeroize> Drop for Shared<T> {
+    fn drop(&mut self) {
+        self.0
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .zeroize();
+    }
+}
+
+impl<T: Zeroize> SharedProtected<T> {
+    /// The number of handles to the shared value (including this one).
+    pub fn handle_count(&self) -> usize {
+        Arc::strong_count(&self.0)
+    }
+}
+
+impl<T> SharedProtected<T>
+where
+    T: Controlled + Zeroize,
+{
+    /// Create a new [SharedProtected] from an inner value.
+    pub fn new(x: T::Inner) -> Self {
+        Self::from_controlled(T::new(x))
+    }
+
+    /// Create a new [SharedProtected] from an existing [Controlled] value.
+    pub fn from_controlled(x: T) -> Self {
+        Self(Arc::new(Shared(RwLock::new(x))))
+    }
+
+    /// Call a closure with a [ProtectedRef] to the inner value while holding a read lock.
+    ///
+    /// ```
+    /// use vitaminc_protected::{Protected, ProtectedWriter, SharedProtected};
+    ///
+    /// let x: SharedProtected<Protected<String>> = SharedProtected::new("secret".to_string());
+    /// let mut writer = ProtectedWriter::new(Vec::new());
+    /// x.with_protected_ref(|s| writer.write_protected(s)).unwrap();
+    /// assert_eq!(writer.into_inner(), b"secret");
+    /// ```
+    pub fn with_protected_ref<A, R, F>(&self, f: F) -> R
+    where
+        A: ?Sized,
+        T: for<'a> AsProtectedRef<'a, A>,
+        F: FnOnce(ProtectedRef<'_, A>) -> R,
+    {
+        let guard = self.read_guard();
+        f(guard.as_protected_ref())
+    }
+
+    /// Call a closure with a reference to the [Controlled] value while holding a read lock.
+    pub fn read<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        f(&self.read_guard())
+    }
+
+    /// Call a closure with a mutable reference to the [Controlled] value while holding a write lock.
+    pub fn write<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut T) -> R,
+    {
+        f(&mut self.write_guard())
+    }
+
+    /// Replace the value for all handles.
+    /// The previous value is zeroized before the write lock is released.
+    pub fn rotate(&self, new: T) {
+        let mut guard = self.write_guard();
+        let mut old = std::mem::replace(&mut *guard, new);
+        old.zeroize();
+    }
+
+    fn read_guard(&self) -> RwLockReadGuard<'_, T> {
+        self.0 .0.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write_guard(&self) -> RwLockWriteGuard<'_, T> {
+        self.0 .0.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<T> From<T> for SharedProtected<T>
+where
+    T: Controlled + Zeroize,
+{
+    fn from(x: T) -> Self {
+        Self::from_controlled(x)
+    }
+}
+
+impl<T: Zeroize> Clone for SharedProtected<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+/// The lock is not waited on so formatting a handle while it is being written to can't deadlock.
+impl<T: Controlled + Zeroize + Debug> Debug for SharedProtected<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut s = f.debug_struct("SharedProtected");
+        match self.0 .0.try_read() {
+            Ok(guard) => s.field("inner", &*guard),
+            Err(_) => s.field("inner", &format_args!("<locked>")),
+        };
+        s.field("handles", &self.handle_count()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Equatable, Protected};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_send_sync() {
+        assert_send_sync::<SharedProtected<Protected<[u8; 32]>>>();
+        assert_send_sync::<SharedProtected<Equatable<Protected<Vec<u8>>>>>();
+    }
+
+    #[test]
+    fn test_clone_shares_value() {
+        let x: SharedProtected<Protected<u8>> = SharedProtected::new(1);
+        let y = x.clone();
+        assert_eq!(x.handle_count(), 2);
+        y.write(|v| v.update(|v| *v += 1));
+        assert_eq!(x.read(|v| v.risky_unwrap()), 2);
+        drop(y);
+        assert_eq!(x.handle_count(), 1);
+    }
+
+    #[test]
+    fn test_rotate_across_threads() {
+        let key: SharedProtected<Protected<[u8; 4]>> = SharedProtected::new([1; 4]);
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let key = key.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        // Readers must never see a mix of the old and new keys
+                        let k = key.read(|k| k.risky_unwrap());
+                        assert!(k == [1; 4] || k == [2; 4]);
+                    }
+                })
+            })
+            .collect();
+        key.rotate(Protected::new([2; 4]));
+        handles.into_iter().for_each(|h| h.join().unwrap());
+        assert_eq!(key.read(|k| k.risky_unwrap()), [2; 4]);
+    }
+
+    /// Counts how many times it has been zeroized.
+    struct Tracked(Arc<AtomicUsize>);
+
+    impl Zeroize for Tracked {
+        fn zeroize(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_zeroized_on_last_drop() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let x: SharedProtected<Protected<Tracked>> = SharedProtected::new(Tracked(count.clone()));
+        let y = x.clone();
+        drop(x);
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+        drop(y);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_rotate_zeroizes_old_value() {
+        let old = Arc::new(AtomicUsize::new(0));
+        let new = Arc::new(AtomicUsize::new(0));
+        let x: SharedProtected<Protected<Tracked>> = SharedProtected::new(Tracked(old.clone()));
+        x.rotate(Protected::new(Tracked(new.clone())));
+        assert_eq!(old.load(Ordering::SeqCst), 1);
+        assert_eq!(new.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_opaque_debug() {
+        let x: SharedProtected<Protected<u8>> = SharedProtected::new(1);
+        assert_eq!(
+            format!("{x:?}"),
+            "SharedProtected { inner: Protected<u8> { ... }, handles: 1 }"
+        );
+    }
+}
@@ -1,17 +1,295 @@
+//! Allows the use of a [Protected] unsigned integer or [Protected] range to index slices and `Vec`s
+//! (and arrays, via the standard library's `Index` impl for `[T; N]`).
+//!
+//! Out of bounds indices panic without including the index in the message.
+//!
+//! Note that this accesses memory at the (secret) index, which can be observed with cache timing.
+//! Use [ObliviousIndex] when the access pattern must not depend on the index.
+//!
+//! # Example
+//!
+//! ```
+//! use vitaminc_protected::{Controlled, Protected};
+//!
+//! let mut x = [1u8, 2, 3, 4];
+//! x[Protected::new(0u8)] = 5;
+//! assert_eq!(x[Protected::new(3u32)], 4);
+//!
+//! let v = vec![1u8, 2, 3, 4];
+//! let range = Protected::range(Protected::new(1), Protected::new(3));
+//! assert_eq!(v[range], [2, 3]);
+//! ```
 use crate::Protected;
-use std::ops::{Index, IndexMut};
+use core::ops::{Index, IndexMut, Range};
+use subtle::{
+    Choice, ConditionallySelectable, ConstantTimeEq, ConstantTimeGreater, ConstantTimeLess,
+};
+use zeroize::Zeroize;
 
-/// Allows the use a of a Paranoid usize to index an array.
-impl<const N: usize, T> Index<Protected<usize>> for [T; N] {
-    type Output = T;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 
-    fn index(&self, index: Protected<usize>) -> &Self::Output {
-        &self[index.0]
+macro_rules! impl_index {
+    ($($type:ty),+) => {
+        $(
+            impl<T> Index<Protected<$type>> for [T] {
+                type Output = T;
+
+                fn index(&self, index: Protected<$type>) -> &Self::Output {
+                    let len = self.len();
+                    self.get(to_index(index.0))
+                        .unwrap_or_else(|| out_of_bounds(len))
+                }
+            }
+
+            impl<T> IndexMut<Protected<$type>> for [T] {
+                fn index_mut(&mut self, index: Protected<$type>) -> &mut Self::Output {
+                    let len = self.len();
+                    self.get_mut(to_index(index.0))
+                        .unwrap_or_else(|| out_of_bounds(len))
+                }
+            }
+
+            #[cfg(feature = "alloc")]
+            impl<T> Index<Protected<$type>> for Vec<T> {
+                type Output = T;
+
+                fn index(&self, index: Protected<$type>) -> &Self::Output {
+                    &self.as_slice()[index]
+                }
+            }
+
+            #[cfg(feature = "alloc")]
+            impl<T> IndexMut<Protected<$type>> for Vec<T> {
+                fn index_mut(&mut self, index: Protected<$type>) -> &mut Self::Output {
+                    &mut self.as_mut_slice()[index]
+                }
+            }
+        )+
+    };
+}
+
+impl_index!(u8, u16, u32, u64, u128, usize);
+
+impl<T> Index<Protected<Range<usize>>> for [T] {
+    type Output = [T];
+
+    fn index(&self, index: Protected<Range<usize>>) -> &Self::Output {
+        let len = self.len();
+        if !ct_range_in_bounds(&index.0, len) {
+            out_of_bounds(len)
+        }
+        // SAFETY: the range was checked above
+        unsafe { self.get_unchecked(index.0) }
+    }
+}
+
+impl<T> IndexMut<Protected<Range<usize>>> for [T] {
+    fn index_mut(&mut self, index: Protected<Range<usize>>) -> &mut Self::Output {
+        let len = self.len();
+        if !ct_range_in_bounds(&index.0, len) {
+            out_of_bounds(len)
+        }
+        // SAFETY: the range was checked above
+        unsafe { self.get_unchecked_mut(index.0) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Index<Protected<Range<usize>>> for Vec<T> {
+    type Output = [T];
+
+    fn index(&self, index: Protected<Range<usize>>) -> &Self::Output {
+        &self.as_slice()[index]
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> IndexMut<Protected<Range<usize>>> for Vec<T> {
+    fn index_mut(&mut self, index: Protected<Range<usize>>) -> &mut Self::Output {
+        &mut self.as_mut_slice()[index]
     }
 }
 
-impl<const N: usize, T> IndexMut<Protected<usize>> for [T; N] {
-    fn index_mut(&mut self, index: Protected<usize>) -> &mut Self::Output {
-        &mut self[index.0]
+/// Constant time table lookups with a [Protected] index.
+///
+/// Every element is read (or written) and the one at the index is chosen with a masked selection
+/// so the memory access pattern and timing don't depend on the index.
+/// This makes a lookup `O(N)` rather than `O(1)`, so it is best suited to small tables like S-boxes.
+///
+/// Whether the index is in bounds is not secret: an out of bounds index returns `None` (or `false`).
+///
+/// # Example
+///
+/// ```
+/// use vitaminc_protected::{slice_index::ObliviousIndex, Controlled, Protected};
+///
+/// let mut table = [10u8, 20, 30, 40];
+/// let x = table.oblivious_get(Protected::new(2)).unwrap();
+/// assert_eq!(x.risky_unwrap(), 30);
+///
+/// assert!(table.oblivious_set(Protected::new(1), Protected::new(21)));
+/// assert_eq!(table, [10, 21, 30, 40]);
+///
+/// assert!(table.oblivious_get(Protected::new(4)).is_none());
+/// ```
+pub trait ObliviousIndex<T> {
+    /// Read the element at `index` by scanning the whole table.
+    fn oblivious_get(&self, index: Protected<usize>) -> Option<Protected<T>>;
+
+    /// Overwrite the element at `index` with `value` by conditionally assigning every element.
+    /// Returns `false` (and leaves the table unchanged) if `index` is out of bounds.
+    fn oblivious_set(&mut self, index: Protected<usize>, value: Protected<T>) -> bool;
+}
+
+impl<T> ObliviousIndex<T> for [T]
+where
+    T: ConditionallySelectable + Default + Zeroize,
+{
+    fn oblivious_get(&self, index: Protected<usize>) -> Option<Protected<T>> {
+        let mut out = Protected(T::default());
+        for (i, x) in self.iter().enumerate() {
+            out.0.conditional_assign(x, i.ct_eq(&index.0));
+        }
+        ct_in_bounds(index.0, self.len()).then_some(out)
+    }
+
+    fn oblivious_set(&mut self, index: Protected<usize>, value: Protected<T>) -> bool {
+        for (i, x) in self.iter_mut().enumerate() {
+            x.conditional_assign(&value.0, i.ct_eq(&index.0));
+        }
+        ct_in_bounds(index.0, self.len())
+    }
+}
+
+impl<const N: usize, T> ObliviousIndex<T> for [T; N]
+where
+    T: ConditionallySelectable + Default + Zeroize,
+{
+    fn oblivious_get(&self, index: Protected<usize>) -> Option<Protected<T>> {
+        self.as_slice().oblivious_get(index)
+    }
+
+    fn oblivious_set(&mut self, index: Protected<usize>, value: Protected<T>) -> bool {
+        self.as_mut_slice().oblivious_set(index, value)
+    }
+}
+
+/// Returns `index < len` without branching on `index`.
+pub(crate) fn ct_in_bounds(index: usize, len: usize) -> bool {
+    (index as u64).ct_lt(&(len as u64)).into()
+}
+
+/// Returns `range.start <= range.end && range.end <= len` without branching on the range.
+pub(crate) fn ct_range_in_bounds(range: &Range<usize>, len: usize) -> bool {
+    let start = range.start as u64;
+    let end = range.end as u64;
+    let valid: Choice = !start.ct_gt(&end) & !end.ct_gt(&(len as u64));
+    valid.into()
+}
+
+/// Converts an unsigned index to a `usize`.
+/// Indices that don't fit (e.g. a `u64` on 32 bit targets) become `usize::MAX` which is always out of bounds.
+pub(crate) fn to_index<I: TryInto<usize>>(index: I) -> usize {
+    index.try_into().unwrap_or(usize::MAX)
+}
+
+/// Panics without revealing the index.
+#[track_caller]
+pub(crate) fn out_of_bounds(len: usize) -> ! {
+    panic!("protected index out of bounds: {len}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Controlled;
+    use std::vec;
+
+    #[test]
+    fn test_index() {
+        let mut x = [1u8, 2, 3];
+        assert_eq!(x[Protected::new(1usize)], 2);
+        x[Protected::new(2usize)] = 4;
+        assert_eq!(x, [1, 2, 4]);
+    }
+
+    #[test]
+    fn test_index_widths() {
+        let mut x = vec![1u8, 2, 3];
+        assert_eq!(x[Protected::new(0u8)], 1);
+        assert_eq!(x[Protected::new(1u16)], 2);
+        assert_eq!(x[Protected::new(2u32)], 3);
+        x[Protected::new(0u64)] = 7;
+        assert_eq!(x.as_slice()[Protected::new(0usize)], 7);
+        assert_eq!(x[Protected::new(1u128)], 2);
+    }
+
+    #[test]
+    fn test_index_range() {
+        let mut x = [1u8, 2, 3, 4];
+        let range = || Protected::range(Protected::new(1), Protected::new(3));
+        assert_eq!(x[range()], [2, 3]);
+        x[range()].fill(0);
+        assert_eq!(x, [1, 0, 0, 4]);
+        assert!(x[Protected::range(Protected::new(4), Protected::new(4))].is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "protected index out of bounds: 3")]
+    fn test_index_out_of_bounds() {
+        let x = Vec::from([1u8, 2, 3]);
+        let _ = x[Protected::new(3u8)];
+    }
+
+    #[test]
+    #[should_panic(expected = "protected index out of bounds: 4")]
+    fn test_index_reversed_range() {
+        let x = [1u8, 2, 3, 4];
+        let _ = &x[Protected::range(Protected::new(3), Protected::new(2))];
+    }
+
+    #[test]
+    fn test_oblivious_get() {
+        let table: [u32; 16] = std::array::from_fn(|i| i as u32 * 3);
+        for i in 0..16 {
+            let x = table.oblivious_get(Protected::new(i)).unwrap();
+            assert_eq!(x.risky_unwrap(), i as u32 * 3);
+        }
+        assert!(table.oblivious_get(Protected::new(16)).is_none());
+        assert!(table.oblivious_get(Protected::new(usize::MAX)).is_none());
+    }
+
+    #[test]
+    fn test_oblivious_get_slice() {
+        let table: Vec<u8> = vec![5, 6, 7];
+        let x = table.as_slice().oblivious_get(Protected::new(2)).unwrap();
+        assert_eq!(x.risky_unwrap(), 7);
+        assert!([0u8; 0].oblivious_get(Protected::new(0)).is_none());
+    }
+
+    #[test]
+    fn test_oblivious_set() {
+        let mut table = [0u64; 4];
+        assert!(table.oblivious_set(Protected::new(3), Protected::new(9)));
+        assert_eq!(table, [0, 0, 0, 9]);
+        assert!(!table.oblivious_set(Protected::new(4), Protected::new(1)));
+        assert_eq!(table, [0, 0, 0, 9]);
+    }
+
+    #[test]
+    fn test_ct_in_bounds() {
+        assert!(ct_in_bounds(0, 1));
+        assert!(!ct_in_bounds(1, 1));
+        assert!(!ct_in_bounds(0, 0));
+        assert!(ct_in_bounds(usize::MAX - 1, usize::MAX));
+    }
+
+    #[test]
+    fn test_ct_range_in_bounds() {
+        assert!(ct_range_in_bounds(&(0..0), 0));
+        assert!(ct_range_in_bounds(&(1..4), 4));
+        assert!(!ct_range_in_bounds(&(1..5), 4));
+        assert!(!ct_range_in_bounds(&Range { start: 3, end: 2 }, 4));
     }
 }
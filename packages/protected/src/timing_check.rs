@@ -0,0 +1,329 @@
+//! A statistical timing harness for checking that comparisons run in constant time.
+//!
+//! With the `timing-check` feature enabled, [TimingHarness] measures an operation on inputs from two classes
+//! (in the style of [dudect](https://eprint.iacr.org/2016/1123.pdf)): a _fixed_ class and a _random_ class,
+//! interleaved in random order. Welch's t-test is then applied to the two timing distributions.
+//! A `|t|` above [T_THRESHOLD] means the timing depends on the input, i.e. the operation leaks.
+//!
+//! For comparisons, the fixed class compares a secret with itself and the random class compares it
+//! with random values, so a comparison that returns early on the first differing byte is easy to spot.
+//! [check_constant_time_eq] does this for any [ConstantTimeEq] type and [check_all] runs it
+//! for every implementation in this crate.
+//!
+//! Timing measurements are noisy. Run checks with optimizations enabled (`--release`),
+//! on an otherwise idle machine and with enough samples to be meaningful.
+//!
+//! # Example
+//!
+//! Not run as a doctest because timing results aren't reliable in unoptimized builds.
+//!
+//! ```no_run
+//! use vitaminc_protected::timing_check::{check_constant_time_eq, TimingHarness};
+//!
+//! let harness = TimingHarness::new().samples(10_000);
+//!
+//! // `==` on slices returns as soon as a byte differs
+//! let secret = vec![7u8; 1024];
+//! let report = harness.run(
+//!     |class| if class.is_fixed() { secret.clone() } else { vec![0u8; 1024] },
+//!     |input| secret == *input,
+//! );
+//! assert!(report.is_leaky());
+//!
+//! let report = check_constant_time_eq(&harness, [7u8; 32], rand::random::<[u8; 32]>);
+//! assert!(!report.is_leaky());
+//! ```
+use crate::{ConstantTimeEq, Equatable, Protected};
+use rand::{distributions::Alphanumeric, Rng};
+use std::{hint::black_box, num::NonZeroU16, time::Instant};
+
+/// The `|t|` value above which an operation is considered to leak timing information (as used by dudect).
+pub const T_THRESHOLD: f64 = 4.5;
+
+/// Measurements above this percentile are discarded to reduce noise (e.g. from interrupts).
+const CROP_PERCENTILE: f64 = 0.95;
+
+/// The class of an input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Class {
+    Fixed,
+    Random,
+}
+
+impl Class {
+    pub fn is_fixed(&self) -> bool {
+        matches!(self, Class::Fixed)
+    }
+}
+
+/// The result of a timing check.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimingReport {
+    /// Welch's t statistic of the fixed and random class timings.
+    pub t: f64,
+    /// The number of measurements used (after cropping outliers).
+    pub samples: usize,
+}
+
+impl TimingReport {
+    /// Returns `true` if `|t|` exceeds [T_THRESHOLD].
+    pub fn is_leaky(&self) -> bool {
+        self.t.abs() > T_THRESHOLD
+    }
+}
+
+/// Measures an operation on inputs of two [Class]es and compares the timings.
+#[derive(Clone, Copy, Debug)]
+pub struct TimingHarness {
+    samples: usize,
+    batch: usize,
+}
+
+impl Default for TimingHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimingHarness {
+    /// Create a harness that takes 100,000 measurements of 16 calls each.
+    pub fn new() -> Self {
+        Self {
+            samples: 100_000,
+            batch: 16,
+        }
+    }
+
+    /// Set the number of measurements.
+    pub fn samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Set how many times the operation is called per measurement.
+    /// Larger batches make fast operations measurable with a coarse clock.
+    pub fn batch(mut self, batch: usize) -> Self {
+        self.batch = batch.max(1);
+        self
+    }
+
+    /// Generate an input for each measurement with `input` and time `op` on it.
+    /// Inputs are generated before any measurements are taken.
+    pub fn run<I, R, G, F>(&self, mut input: G, mut op: F) -> TimingReport
+    where
+        G: FnMut(Class) -> I,
+        F: FnMut(&I) -> R,
+    {
+        let mut rng = rand::thread_rng();
+        let classes: Vec<Class> = (0..self.samples)
+            .map(|_| {
+                if rng.gen() {
+                    Class::Fixed
+                } else {
+                    Class::Random
+                }
+            })
+            .collect();
+        let inputs: Vec<I> = classes.iter().map(|class| input(*class)).collect();
+
+        let timings: Vec<f64> = inputs
+            .iter()
+            .map(|input| {
+                let start = Instant::now();
+                for _ in 0..self.batch {
+                    black_box(op(black_box(input)));
+                }
+                start.elapsed().as_nanos() as f64
+            })
+            .collect();
+
+        let cutoff = percentile(&timings, CROP_PERCENTILE);
+        let (fixed, random): (Vec<_>, Vec<_>) = classes
+            .into_iter()
+            .zip(timings)
+            .filter(|(_, timing)| *timing <= cutoff)
+            .partition(|(class, _)| class.is_fixed());
+        let fixed: Vec<f64> = fixed.into_iter().map(|(_, t)| t).collect();
+        let random: Vec<f64> = random.into_iter().map(|(_, t)| t).collect();
+
+        TimingReport {
+            t: welch_t(&fixed, &random),
+            samples: fixed.len() + random.len(),
+        }
+    }
+}
+
+/// Check a [ConstantTimeEq] implementation by comparing `secret` with itself (fixed class)
+/// and with values from `random` (random class).
+pub fn check_constant_time_eq<T, G>(
+    harness: &TimingHarness,
+    secret: T,
+    mut random: G,
+) -> TimingReport
+where
+    T: ConstantTimeEq + Clone,
+    G: FnMut() -> T,
+{
+    harness.run(
+        |class| match class {
+            Class::Fixed => secret.clone(),
+            Class::Random => random(),
+        },
+        |input| secret.constant_time_eq(input),
+    )
+}
+
+/// Run [check_constant_time_eq] for every [ConstantTimeEq] implementation in this crate
+/// and return the name of each type with its report.
+pub fn check_all(harness: &TimingHarness) -> Vec<(&'static str, TimingReport)> {
+    macro_rules! check_ints {
+        ($($type:ty),+) => {
+            vec![$(
+                (stringify!($type), check_constant_time_eq(harness, rand::random::<$type>(), rand::random::<$type>)),
+            )+]
+        };
+    }
+
+    let mut reports = check_ints!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128);
+    let mut rng = rand::thread_rng();
+    let nonzero = NonZeroU16::new(rng.gen_range(1..=u16::MAX)).unwrap_or(NonZeroU16::MIN);
+    reports.push((
+        "NonZeroU16",
+        check_constant_time_eq(harness, nonzero, || {
+            NonZeroU16::new(rand::thread_rng().gen_range(1..=u16::MAX)).unwrap_or(NonZeroU16::MIN)
+        }),
+    ));
+    reports.push((
+        "[u8; 32]",
+        check_constant_time_eq(harness, rand::random::<[u8; 32]>(), rand::random),
+    ));
+
+    let secret: Vec<u8> = (0..256).map(|_| rng.gen()).collect();
+    reports.push((
+        "[u8]",
+        harness.run(
+            |class| match class {
+                Class::Fixed => secret.clone(),
+                Class::Random => (0..256).map(|_| rand::random()).collect(),
+            },
+            |input| secret.as_slice().constant_time_eq(input.as_slice()),
+        ),
+    ));
+
+    let secret = random_string(64);
+    reports.push((
+        "str",
+        harness.run(
+            |class| match class {
+                Class::Fixed => secret.clone(),
+                Class::Random => random_string(64),
+            },
+            |input| secret.as_str().constant_time_eq(input.as_str()),
+        ),
+    ));
+    reports.push((
+        "String",
+        check_constant_time_eq(harness, secret.clone(), || random_string(64)),
+    ));
+
+    let bytes: [u8; 32] = rand::random();
+    let secret = Equatable::<Protected<[u8; 32]>>::new(bytes);
+    reports.push((
+        "Equatable<Protected<[u8; 32]>>",
+        harness.run(
+            |class| match class {
+                Class::Fixed => Equatable::<Protected<[u8; 32]>>::new(bytes),
+                Class::Random => Equatable::new(rand::random()),
+            },
+            |input| secret.constant_time_eq(input),
+        ),
+    ));
+
+    reports
+}
+
+fn random_string(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+fn percentile(values: &[f64], p: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted[((sorted.len() - 1) as f64 * p) as usize]
+}
+
+/// Welch's t statistic for two samples (zero if either sample is too small or has no variance).
+fn welch_t(a: &[f64], b: &[f64]) -> f64 {
+    fn mean_var(x: &[f64]) -> (f64, f64) {
+        let n = x.len() as f64;
+        let mean = x.iter().sum::<f64>() / n;
+        let var = x.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        (mean, var)
+    }
+
+    if a.len() < 2 || b.len() < 2 {
+        return 0.0;
+    }
+    let (mean_a, var_a) = mean_var(a);
+    let (mean_b, var_b) = mean_var(b);
+    let se = (var_a / a.len() as f64 + var_b / b.len() as f64).sqrt();
+    if se == 0.0 {
+        return 0.0;
+    }
+    (mean_a - mean_b) / se
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_welch_t() {
+        assert_eq!(welch_t(&[1.0, 1.0], &[1.0, 1.0]), 0.0);
+        assert_eq!(welch_t(&[1.0], &[2.0, 3.0]), 0.0);
+        let t = welch_t(&[1.0, 2.0, 3.0, 4.0], &[2.0, 3.0, 4.0, 5.0]);
+        assert!((t + 1.0954).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values: Vec<f64> = (0..=100).map(f64::from).collect();
+        assert_eq!(percentile(&values, 0.95), 95.0);
+        assert_eq!(percentile(&[], 0.95), 0.0);
+    }
+
+    /// Timing based, so ignored by default like `test_crate_impls_are_constant_time`.
+    #[test]
+    #[ignore]
+    fn test_detects_short_circuit() {
+        let harness = TimingHarness::new().samples(10_000).batch(1);
+        let secret = vec![7u8; 2048];
+        let report = harness.run(
+            |class| match class {
+                Class::Fixed => secret.clone(),
+                Class::Random => vec![0u8; 2048],
+            },
+            |input| secret == *input,
+        );
+        assert!(report.is_leaky(), "{report:?}");
+    }
+
+    /// Timing is too noisy for unoptimized builds and shared CI machines so this is ignored by default.
+    /// Run with `cargo test --release --features timing-check -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_crate_impls_are_constant_time() {
+        let leaky: Vec<_> = check_all(&TimingHarness::new())
+            .into_iter()
+            .filter(|(_, report)| report.is_leaky())
+            .collect();
+        assert!(leaky.is_empty(), "{leaky:?}");
+    }
+}
@@ -1,11 +1,21 @@
 use serde::{Serialize, Serializer};
 
-use crate::{exportable::SafeSerialize, private::ControlledPrivate, Controlled, Protected};
-use std::marker::PhantomData;
+use crate::{
+    exportable::SafeSerialize, private::ControlledPrivate, AccessError, Controlled, Protected,
+};
+use core::{fmt::Debug, marker::PhantomData};
 
 // TODO: Docs, explain compile time
 pub struct Usage<T, Scope = DefaultScope>(pub(crate) T, pub(crate) PhantomData<Scope>);
 
+/// Debug is implemented manually so that the scope doesn't need to implement `Debug`.
+/// The inner value is never printed as `T` is always a [Controlled] type with an opaque `Debug`.
+impl<T: Debug, S> Debug for Usage<T, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Usage").field(&self.0).finish()
+    }
+}
+
 impl<T, S> Usage<T, S> {
     pub fn new(x: <Usage<T, S> as ControlledPrivate>::Inner) -> Self
     where
@@ -30,17 +40,46 @@ impl<T: ControlledPrivate, Scope> ControlledPrivate for Usage<T, Scope> {
     fn inner_mut(&mut self) -> &mut Self::Inner {
         self.0.inner_mut()
     }
-}
 
-impl<T, Scope> Controlled for Usage<T, Scope>
-where
-    T: Controlled,
-{
-    fn risky_unwrap(self) -> Self::Inner {
-        self.0.risky_unwrap()
+    fn into_inner(self) -> Self::Inner {
+        self.0.into_inner()
+    }
+
+    fn with_inner<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&Self::Inner) -> R,
+    {
+        self.0.with_inner(f)
+    }
+
+    fn try_with_inner<R, F>(&self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&Self::Inner) -> R,
+    {
+        self.0.try_with_inner(f)
+    }
+
+    fn with_inner_mut<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Self::Inner) -> R,
+    {
+        self.0.with_inner_mut(f)
+    }
+
+    fn try_with_inner_mut<R, F>(&mut self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&mut Self::Inner) -> R,
+    {
+        self.0.try_with_inner_mut(f)
+    }
+
+    fn scope_name() -> Option<&'static str> {
+        Some(core::any::type_name::<Scope>())
     }
 }
 
+impl<T, Scope> Controlled for Usage<T, Scope> where T: Controlled {}
+
 /// Marker trait for a type that defines a usage scope
 pub trait Scope {}
 
@@ -70,13 +109,16 @@ where
     where
         S: Serializer,
     {
-        self.inner().safe_serialize(serializer)
+        #[cfg(feature = "audit")]
+        crate::audit::record::<Self>(crate::audit::ExposureKind::Export);
+        self.with_inner(|x| x.safe_serialize(serializer))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::format;
 
     struct MyScope;
     impl Scope for MyScope {}
@@ -89,6 +131,12 @@ mod tests {
         true
     }
 
+    #[test]
+    fn test_opaque_debug() {
+        let x: Usage<Protected<[u8; 32]>, MyScope> = Usage::new([0u8; 32]);
+        assert_eq!(format!("{:?}", x), "Usage(Protected<[u8; 32]> { ... })");
+    }
+
     #[test]
     fn test_usage_for_default_scope() {
         let x: Usage<Protected<[u8; 32]>, DefaultScope> = Usage::new([0u8; 32]);
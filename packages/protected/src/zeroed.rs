@@ -1,29 +1,31 @@
-use crate::{Equatable, Exportable, Protected};
+use crate::{Equatable, Exportable, Protected, Usage};
+use core::marker::PhantomData;
 
-/// Similar to `Default`, but doesn't rely on the standard library,
-/// is only implemented for Paranoid types, and covers array sizes up to 1024.
+/// Similar to `Default`, but doesn't rely on the standard library
+/// and is only implemented for Paranoid types and the values they wrap.
+/// Arrays of any size (and nesting) are supported.
 pub trait Zeroed {
     fn zeroed() -> Self;
 }
 
-macro_rules! impl_zeroed_for_array {
-    ($t:ty, $($N:expr),+) => {
+macro_rules! impl_zeroed_for_literal {
+    ($($t:ty),+) => {
         $(
-            impl Zeroed for [$t; $N] {
+            impl Zeroed for $t {
                 fn zeroed() -> Self {
-                    [0; $N]
+                    0
                 }
             }
         )+
     };
 }
 
-macro_rules! impl_zeroed_for_literal {
-    ($($t:ty),+) => {
+macro_rules! impl_zeroed_for_tuple {
+    ($(($($T:ident),+)),+) => {
         $(
-            impl Zeroed for $t {
+            impl<$($T: Zeroed),+> Zeroed for ($($T,)+) {
                 fn zeroed() -> Self {
-                    0
+                    ($($T::zeroed(),)+)
                 }
             }
         )+
@@ -57,16 +59,72 @@ where
     }
 }
 
-impl_zeroed_for_array!(
-    u8, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
-    26, 27, 28, 29, 30, 31, 32, 48, 64, 128, 256, 512, 1024
-);
-impl_zeroed_for_array!(
-    u16, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
-    26, 27, 28, 29, 30, 31, 32, 48, 64, 128, 256, 512
-);
-impl_zeroed_for_array!(
-    u32, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
-    26, 27, 28, 29, 30, 31, 32, 48, 64, 128, 256
+impl<T, S> Zeroed for Usage<T, S>
+where
+    T: Zeroed,
+{
+    fn zeroed() -> Self {
+        Usage(T::zeroed(), PhantomData)
+    }
+}
+
+impl<T, const N: usize> Zeroed for [T; N]
+where
+    T: Zeroed,
+{
+    fn zeroed() -> Self {
+        core::array::from_fn(|_| T::zeroed())
+    }
+}
+
+impl<T> Zeroed for Option<T> {
+    fn zeroed() -> Self {
+        None
+    }
+}
+
+impl Zeroed for bool {
+    fn zeroed() -> Self {
+        false
+    }
+}
+
+impl_zeroed_for_literal!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+impl_zeroed_for_tuple!(
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G),
+    (A, B, C, D, E, F, G, H)
 );
-impl_zeroed_for_literal!(u8, u16, u32, u64, u128);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Controlled;
+
+    #[test]
+    fn test_arrays() {
+        assert_eq!(<[u8; 33]>::zeroed(), [0; 33]);
+        assert_eq!(<[i32; 4]>::zeroed(), [0; 4]);
+        assert_eq!(<[[u64; 3]; 2000]>::zeroed(), [[0; 3]; 2000]);
+        assert_eq!(<[bool; 2]>::zeroed(), [false; 2]);
+    }
+
+    #[test]
+    fn test_option_and_tuples() {
+        assert_eq!(<Option<[u8; 4]>>::zeroed(), None);
+        assert_eq!(<(u8, [i16; 2], bool)>::zeroed(), (0, [0, 0], false));
+    }
+
+    #[test]
+    fn test_adapter_stacks() {
+        let x: Equatable<Exportable<Protected<[u8; 33]>>> = Zeroed::zeroed();
+        assert_eq!(x.risky_unwrap(), [0; 33]);
+        let x: Usage<Protected<[i64; 5]>> = Zeroed::zeroed();
+        assert_eq!(x.risky_unwrap(), [0; 5]);
+    }
+}
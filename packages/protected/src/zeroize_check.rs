@@ -0,0 +1,218 @@
+//! A test-support allocator that checks freed memory has been zeroized.
+//!
+//! With the `zeroize-check` feature enabled, install [ZeroizeCheckAllocator] as the global allocator
+//! of a test binary (typically an integration test in `tests/`) and wrap code that handles secrets
+//! in [check_zeroized] or [assert_zeroized].
+//! Every allocation freed on the current thread while the closure runs is scanned
+//! and any that still contain non-zero bytes are reported.
+//!
+//! Tracking is per-thread and only active inside the closure, so the allocator has no effect on
+//! the rest of the test suite beyond a thread-local lookup on each free.
+//! Reallocations are checked too: the old block of a growing `Vec` is freed without being zeroized
+//! unless capacity is reserved up front.
+//! Every block is zero-filled when it is allocated (so that freed blocks can be scanned without reading
+//! uninitialized memory), which means unused capacity is never reported.
+//!
+//! Keep the closure focused on the code under test, as any non-secret allocations it frees
+//! (e.g. formatting a `String`) are reported as well.
+//!
+//! # Example
+//!
+//! ```
+//! use vitaminc_protected::{zeroize_check, Controlled, Protected};
+//! use zeroize::Zeroize;
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: zeroize_check::ZeroizeCheckAllocator = zeroize_check::ZeroizeCheckAllocator;
+//!
+//! // Zeroizing before dropping wipes the heap buffer
+//! zeroize_check::assert_zeroized(|| {
+//!     let mut key = Protected::new(vec![42u8; 32]);
+//!     key.zeroize();
+//! });
+//!
+//! // Dropping a `Vec` doesn't
+//! let (_, report) = zeroize_check::check_zeroized(|| {
+//!     let key = Protected::new(vec![42u8; 32]);
+//!     drop(key);
+//! });
+//! assert_eq!(report.non_zero_frees, 1);
+//! assert_eq!(report.recorded[0].size, 32);
+//! ```
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::{Cell, RefCell},
+};
+
+/// The maximum number of non-zero frees recorded in a [ZeroizeReport] (all of them are counted).
+pub const MAX_RECORDED: usize = 64;
+
+/// A freed allocation that was not zeroized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NonZeroFree {
+    /// The size of the allocation in bytes.
+    pub size: usize,
+    /// How many of those bytes were not zero.
+    pub non_zero_bytes: usize,
+}
+
+/// The result of running a closure with [check_zeroized].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ZeroizeReport {
+    /// The number of allocations freed by the closure.
+    pub frees: usize,
+    /// The number of freed allocations that were not zeroized.
+    pub non_zero_frees: usize,
+    /// Details of the first [MAX_RECORDED] non-zero frees.
+    pub recorded: Vec<NonZeroFree>,
+}
+
+impl ZeroizeReport {
+    /// Returns `true` if every allocation freed by the closure was zeroized.
+    pub fn is_clean(&self) -> bool {
+        self.non_zero_frees == 0
+    }
+}
+
+/// A global allocator that wraps [System] and, while a [check_zeroized] closure is running,
+/// scans every block freed on the current thread for non-zero bytes.
+pub struct ZeroizeCheckAllocator;
+
+thread_local! {
+    // Const initialized thread locals don't allocate, so they are safe to use in the allocator
+    static ACTIVE: Cell<bool> = const { Cell::new(false) };
+    static FREES: Cell<usize> = const { Cell::new(0) };
+    static NON_ZERO_FREES: Cell<usize> = const { Cell::new(0) };
+    static RECORDED: RefCell<[NonZeroFree; MAX_RECORDED]> = const {
+        RefCell::new([NonZeroFree { size: 0, non_zero_bytes: 0 }; MAX_RECORDED])
+    };
+}
+
+unsafe impl GlobalAlloc for ZeroizeCheckAllocator {
+    // Blocks are zero-filled so that `dealloc` only ever scans initialized memory
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        System.alloc_zeroed(layout)
+    }
+
+    // `realloc` is deliberately not overridden so that the default implementation
+    // allocates the new block through `alloc` (so it is zero-filled)
+    // and frees the old block through `dealloc` (where it is checked).
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if ACTIVE.try_with(Cell::get).unwrap_or(false) {
+            record_free(count_non_zero(ptr, layout.size()), layout.size());
+        }
+        System.dealloc(ptr, layout)
+    }
+}
+
+/// Count the non-zero bytes in a block that is about to be freed.
+///
+/// Every block is zero-filled by [ZeroizeCheckAllocator::alloc] so its bytes are initialized
+/// even if the program never wrote to them. Volatile reads don't make reading uninitialized memory
+/// sound; they only stop the compiler from assuming the contents of a block that is about to be freed.
+/// Bytes the program overwrote with uninitialized data (such as struct padding) are still undefined
+/// to read, so this is only suitable for tests.
+unsafe fn count_non_zero(ptr: *const u8, size: usize) -> usize {
+    (0..size)
+        .filter(|i| ptr.add(*i).read_volatile() != 0)
+        .count()
+}
+
+fn record_free(non_zero_bytes: usize, size: usize) {
+    FREES.set(FREES.get() + 1);
+    if non_zero_bytes == 0 {
+        return;
+    }
+    let index = NON_ZERO_FREES.get();
+    NON_ZERO_FREES.set(index + 1);
+    if index < MAX_RECORDED {
+        RECORDED.with_borrow_mut(|recorded| {
+            recorded[index] = NonZeroFree {
+                size,
+                non_zero_bytes,
+            }
+        });
+    }
+}
+
+/// Enables tracking until dropped (including when the closure panics).
+struct Active;
+
+impl Active {
+    fn start() -> Self {
+        ACTIVE.set(true);
+        Self
+    }
+}
+
+impl Drop for Active {
+    fn drop(&mut self) {
+        ACTIVE.set(false);
+    }
+}
+
+/// Run `f` and report every allocation it freed (on this thread) that was not zeroized.
+///
+/// Returns an empty report if [ZeroizeCheckAllocator] is not the global allocator
+/// (use [assert_zeroized] to catch that).
+///
+/// # Panics
+///
+/// If called from inside another [check_zeroized] closure.
+pub fn check_zeroized<R, F>(f: F) -> (R, ZeroizeReport)
+where
+    F: FnOnce() -> R,
+{
+    assert!(!ACTIVE.get(), "check_zeroized calls can't be nested");
+    FREES.set(0);
+    NON_ZERO_FREES.set(0);
+    let result = {
+        let _active = Active::start();
+        f()
+    };
+
+    let non_zero_frees = NON_ZERO_FREES.get();
+    let recorded =
+        RECORDED.with_borrow(|recorded| recorded[..non_zero_frees.min(MAX_RECORDED)].to_vec());
+    let report = ZeroizeReport {
+        frees: FREES.get(),
+        non_zero_frees,
+        recorded,
+    };
+    (result, report)
+}
+
+/// Run `f` and panic if any allocation it freed was not zeroized.
+///
+/// # Panics
+///
+/// If [ZeroizeCheckAllocator] is not the global allocator, or if any freed allocation contained non-zero bytes.
+pub fn assert_zeroized<R, F>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    assert!(
+        is_installed(),
+        "ZeroizeCheckAllocator is not the global allocator"
+    );
+    let (result, report) = check_zeroized(f);
+    assert!(
+        report.is_clean(),
+        "{} of {} freed allocations were not zeroized: {:?}",
+        report.non_zero_frees,
+        report.frees,
+        report.recorded
+    );
+    result
+}
+
+/// Returns `true` if [ZeroizeCheckAllocator] is the global allocator.
+pub fn is_installed() -> bool {
+    let (_, report) = check_zeroized(|| drop(std::hint::black_box(Box::new(0u8))));
+    report.frees == 1
+}
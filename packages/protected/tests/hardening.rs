@@ -0,0 +1,18 @@
+//! [harden] changes the state of the whole process so it is tested in its own test binary
+//! rather than in the library's unit tests.
+#![cfg(all(feature = "hardening", target_os = "linux"))]
+
+use vitaminc_protected::hardening::{harden, report, HardeningError};
+
+#[test]
+fn test_harden() {
+    let hardened = match harden() {
+        Ok(report) => report,
+        // Tests may be run under a debugger
+        Err(HardeningError::Traced(_)) => report().unwrap(),
+        Err(e) => panic!("{e}"),
+    };
+    assert_eq!(hardened.core_limit, 0);
+    assert!(!hardened.dumpable);
+    assert_eq!(hardened, report().unwrap());
+}
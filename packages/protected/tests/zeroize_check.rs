@@ -0,0 +1,87 @@
+//! [ZeroizeCheckAllocator] must be the global allocator of the test binary
+//! so these tests are kept out of the library's unit tests.
+#![cfg(feature = "zeroize-check")]
+
+use vitaminc_protected::{
+    zeroize_check::{
+        assert_zeroized, check_zeroized, is_installed, NonZeroFree, ZeroizeCheckAllocator,
+        MAX_RECORDED,
+    },
+    Controlled, Protected,
+};
+use zeroize::Zeroize;
+
+#[global_allocator]
+static ALLOCATOR: ZeroizeCheckAllocator = ZeroizeCheckAllocator;
+
+#[test]
+fn test_installed() {
+    assert!(is_installed());
+    // Tracking stops at the end of each check
+    assert!(is_installed());
+}
+
+#[test]
+fn test_zeroized() {
+    assert_zeroized(|| {
+        let mut x = Protected::new(vec![1u8; 64]);
+        x.zeroize();
+    });
+}
+
+#[test]
+fn test_non_zero_free() {
+    let (_, report) = check_zeroized(|| drop(Protected::new(vec![0u8, 1, 2, 3])));
+    assert_eq!(report.frees, 1);
+    assert_eq!(
+        report.recorded,
+        vec![NonZeroFree {
+            size: 4,
+            non_zero_bytes: 3
+        }]
+    );
+}
+
+#[test]
+fn test_realloc_is_checked() {
+    let (_, report) = check_zeroized(|| {
+        let mut x = Protected::new(Vec::with_capacity(1));
+        // Fill the first block before growing so that its contents are known when it is freed
+        x.update(|v| v.push(1u8));
+        x.update(|v| v.extend_from_slice(&[1u8; 16]));
+        x.zeroize();
+    });
+    assert_eq!(report.non_zero_frees, 1);
+    assert_eq!(
+        report.recorded[0],
+        NonZeroFree {
+            size: 1,
+            non_zero_bytes: 1
+        }
+    );
+}
+
+#[test]
+fn test_recorded_is_capped() {
+    let (_, report) = check_zeroized(|| {
+        (0..MAX_RECORDED + 10).for_each(|_| drop(std::hint::black_box(vec![1u8; 8])))
+    });
+    assert_eq!(report.non_zero_frees, MAX_RECORDED + 10);
+    assert_eq!(report.recorded.len(), MAX_RECORDED);
+}
+
+/// `update_with` drops the inner value of `other` without zeroizing it (see the FIXME in `controlled.rs`).
+#[test]
+fn test_update_with_leaks_other() {
+    let mut x = Protected::new([0u8; 4]);
+    let (_, report) = check_zeroized(|| {
+        x.update_with(Protected::new(vec![7u8; 4]), |x, y| x.copy_from_slice(&y));
+    });
+    assert_eq!(report.non_zero_frees, 1);
+}
+
+#[test]
+#[should_panic(expected = "1 of 1 freed allocations were not zeroized")]
+fn test_assert_zeroized_panics() {
+    assert_zeroized(|| drop(Protected::new(vec![1u8; 4])));
+}